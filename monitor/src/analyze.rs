@@ -4,21 +4,37 @@ use std::{
     time::{Duration, Instant},
 };
 
+use chrono::{DateTime, Utc};
 use thiserror::Error;
 
 use crate::{
+    metrics,
     nonce_cache::{NonceCache, NonceCacheError},
     pool::{ObservedTransaction, Pool},
+    relay_api::RelayProvenance,
     types::{Address, BeaconBlock, ExecutionPayload, Transaction, TxHash, U256},
 };
 
+/// Gas charged per blob, as defined by EIP-4844.
+const GAS_PER_BLOB: u64 = 131072;
+/// Maximum blob gas a single block may consume, as defined by EIP-4844.
+const MAX_BLOB_GAS_PER_BLOCK: u64 = 786432;
+/// Minimum possible blob base fee, as defined by EIP-4844.
+const MIN_BLOB_BASE_FEE: u64 = 1;
+/// Divisor controlling how quickly the blob base fee adjusts, as defined by
+/// EIP-4844.
+const BLOB_BASE_FEE_UPDATE_FRACTION: u64 = 3338477;
+
 /// Possible justified reasons why a transaction is not in a block.
 #[derive(Debug, Hash, PartialEq, Eq)]
 pub enum NonInclusionReason {
     NotEnoughSpace,
     BaseFeeTooLow,
     TipTooLow,
-    NonceMismatch,
+    NonceTooLow,
+    NonceInFuture,
+    NotEnoughBlobSpace,
+    BlobBaseFeeTooLow,
 }
 
 #[derive(Debug, Error)]
@@ -42,16 +58,22 @@ async fn check_inclusion(
     transaction: &Transaction,
     beacon_block: &BeaconBlock<Transaction>,
     nonce_cache: &mut NonceCache,
+    inclusion_threshold: &InclusionThreshold,
+    pool_inclusion_threshold: &InclusionThreshold,
 ) -> Result<Option<NonInclusionReason>, InclusionCheckError> {
     let exec = &beacon_block.body.execution_payload;
-    if check_not_enough_space(transaction, exec) {
+    if check_not_enough_blob_space(transaction, exec)? {
+        Ok(Some(NonInclusionReason::NotEnoughBlobSpace))
+    } else if check_not_enough_space(transaction, exec) {
         Ok(Some(NonInclusionReason::NotEnoughSpace))
     } else if check_base_fee_too_low(transaction, exec)? {
         Ok(Some(NonInclusionReason::BaseFeeTooLow))
-    } else if check_tip_too_low(transaction, exec)? {
+    } else if check_blob_base_fee_too_low(transaction, exec)? {
+        Ok(Some(NonInclusionReason::BlobBaseFeeTooLow))
+    } else if check_tip_too_low(transaction, exec, inclusion_threshold, pool_inclusion_threshold)? {
         Ok(Some(NonInclusionReason::TipTooLow))
-    } else if check_nonce_mismatch(transaction, beacon_block, nonce_cache).await? {
-        Ok(Some(NonInclusionReason::NonceMismatch))
+    } else if let Some(reason) = check_nonce_mismatch(transaction, beacon_block, nonce_cache).await? {
+        Ok(Some(reason))
     } else {
         Ok(None)
     }
@@ -78,7 +100,7 @@ fn get_tip(transaction: &Transaction, base_fee: U256) -> Result<U256, Transactio
                 name: String::from("gasPrice"),
             })?;
         Ok(gas_price - base_fee)
-    } else if t == 2 {
+    } else if t == 2 || t == 3 {
         let max_fee_per_gas =
             transaction
                 .max_fee_per_gas
@@ -99,6 +121,64 @@ fn get_tip(transaction: &Transaction, base_fee: U256) -> Result<U256, Transactio
     }
 }
 
+/// Get the effective price per unit of gas a transaction would pay in a
+/// block with given base fee, i.e. what a miner/validator actually receives
+/// plus the base fee burned.
+fn get_effective_gas_price(
+    transaction: &Transaction,
+    base_fee: U256,
+) -> Result<U256, TransactionError> {
+    let t = get_transaction_type(transaction)?;
+    if t == 0 || t == 1 {
+        transaction
+            .gas_price
+            .ok_or(TransactionError::MissingRequiredField {
+                name: String::from("gasPrice"),
+            })
+    } else if t == 2 || t == 3 {
+        let max_fee_per_gas =
+            transaction
+                .max_fee_per_gas
+                .ok_or(TransactionError::MissingRequiredField {
+                    name: String::from("maxFeePerGas"),
+                })?;
+        let max_priority_fee_per_gas =
+            transaction
+                .max_priority_fee_per_gas
+                .ok_or(TransactionError::MissingRequiredField {
+                    name: String::from("maxPriorityFeePerGas"),
+                })?;
+        Ok(min(max_fee_per_gas, base_fee + max_priority_fee_per_gas))
+    } else {
+        Err(TransactionError::UnsupportedType {
+            transaction_type: t,
+        })
+    }
+}
+
+/// Check if `new_value` bumps `old_value` by at least the minimum 12.5%
+/// required by execution clients to accept a replacement transaction.
+fn is_fee_bumped(old_value: U256, new_value: U256) -> bool {
+    new_value >= old_value + old_value / 8
+}
+
+/// Check whether `in_block_tx` is a legitimate fee-bumped replacement of
+/// `pool_tx`, which shares its sender and nonce. Both the tip and the
+/// effective gas price paid at `base_fee` must be bumped by at least the
+/// minimum 12.5% bump rule; otherwise the in-block transaction is a
+/// suspiciously cheap displacement rather than a genuine replacement.
+fn is_legitimate_replacement(
+    pool_tx: &Transaction,
+    in_block_tx: &Transaction,
+    base_fee: U256,
+) -> Result<bool, TransactionError> {
+    let old_tip = get_tip(pool_tx, base_fee)?;
+    let new_tip = get_tip(in_block_tx, base_fee)?;
+    let old_price = get_effective_gas_price(pool_tx, base_fee)?;
+    let new_price = get_effective_gas_price(in_block_tx, base_fee)?;
+    Ok(is_fee_bumped(old_tip, new_tip) && is_fee_bumped(old_price, new_price))
+}
+
 /// Check if there is not enough space left in the block to include the
 /// transaction.
 fn check_not_enough_space(transaction: &Transaction, exec: &ExecutionPayload<Transaction>) -> bool {
@@ -106,108 +186,418 @@ fn check_not_enough_space(transaction: &Transaction, exec: &ExecutionPayload<Tra
     transaction.gas > U256::from(unused_gas.as_u64())
 }
 
-/// Check if the transaction doesn't pay a high enough base fee.
-fn check_base_fee_too_low(
-    transaction: &Transaction,
-    exec: &ExecutionPayload<Transaction>,
-) -> Result<bool, TransactionError> {
+/// Get the maximum base fee a transaction is willing to pay, i.e. `gasPrice`
+/// for legacy transactions or `maxFeePerGas` for 1559 ones.
+fn get_max_base_fee(transaction: &Transaction) -> Result<U256, TransactionError> {
     let t = get_transaction_type(transaction)?;
-    let max_base_fee = if t == 0 || t == 1 {
+    if t == 0 || t == 1 {
         transaction
             .gas_price
             .ok_or(TransactionError::MissingRequiredField {
                 name: String::from("gasPrice"),
-            })?
-    } else if t == 2 {
+            })
+    } else if t == 2 || t == 3 {
         transaction
             .max_fee_per_gas
             .ok_or(TransactionError::MissingRequiredField {
                 name: String::from("maxFeePerGas"),
-            })?
+            })
     } else {
-        return Err(TransactionError::UnsupportedType {
+        Err(TransactionError::UnsupportedType {
             transaction_type: t,
-        });
-    };
+        })
+    }
+}
+
+/// Check if the transaction doesn't pay a high enough base fee.
+fn check_base_fee_too_low(
+    transaction: &Transaction,
+    exec: &ExecutionPayload<Transaction>,
+) -> Result<bool, TransactionError> {
+    let max_base_fee = get_max_base_fee(transaction)?;
     Ok(max_base_fee < exec.base_fee_per_gas)
 }
 
-/// Check if the transaction doesn't pay a high enough tip.
+/// Check if there is not enough room left in the block's blob gas budget for
+/// the transaction's blobs. Non-blob transactions always pass this check.
+fn check_not_enough_blob_space(
+    transaction: &Transaction,
+    exec: &ExecutionPayload<Transaction>,
+) -> Result<bool, TransactionError> {
+    let t = get_transaction_type(transaction)?;
+    if t != 3 {
+        return Ok(false);
+    }
+    let blob_gas_used = exec
+        .blob_gas_used
+        .ok_or(TransactionError::MissingRequiredField {
+            name: String::from("blobGasUsed"),
+        })?
+        .as_u64();
+    let blob_versioned_hashes =
+        transaction
+            .blob_versioned_hashes
+            .as_ref()
+            .ok_or(TransactionError::MissingRequiredField {
+                name: String::from("blobVersionedHashes"),
+            })?;
+    let tx_blob_gas = blob_versioned_hashes.len() as u64 * GAS_PER_BLOB;
+    Ok(blob_gas_used + tx_blob_gas > MAX_BLOB_GAS_PER_BLOCK)
+}
+
+/// Check if the transaction doesn't pay a high enough blob base fee.
+/// Non-blob transactions always pass this check.
+fn check_blob_base_fee_too_low(
+    transaction: &Transaction,
+    exec: &ExecutionPayload<Transaction>,
+) -> Result<bool, TransactionError> {
+    let t = get_transaction_type(transaction)?;
+    if t != 3 {
+        return Ok(false);
+    }
+    let excess_blob_gas = exec
+        .excess_blob_gas
+        .ok_or(TransactionError::MissingRequiredField {
+            name: String::from("excessBlobGas"),
+        })?
+        .as_u64();
+    let max_fee_per_blob_gas =
+        transaction
+            .max_fee_per_blob_gas
+            .ok_or(TransactionError::MissingRequiredField {
+                name: String::from("maxFeePerBlobGas"),
+            })?;
+    let blob_base_fee = get_blob_base_fee(excess_blob_gas);
+    Ok(max_fee_per_blob_gas < blob_base_fee)
+}
+
+/// Derive the blob base fee for a block from its excess blob gas, as defined
+/// by EIP-4844.
+fn get_blob_base_fee(excess_blob_gas: u64) -> U256 {
+    fake_exponential(
+        MIN_BLOB_BASE_FEE,
+        excess_blob_gas,
+        BLOB_BASE_FEE_UPDATE_FRACTION,
+    )
+}
+
+/// Approximate `factor * e ** (numerator / denominator)` using a Taylor
+/// series, as defined by EIP-4844.
+fn fake_exponential(factor: u64, numerator: u64, denominator: u64) -> U256 {
+    let factor = U256::from(factor);
+    let numerator = U256::from(numerator);
+    let denominator = U256::from(denominator);
+
+    let mut i = U256::from(1);
+    let mut output = U256::zero();
+    let mut numerator_accum = factor * denominator;
+    while !numerator_accum.is_zero() {
+        output += numerator_accum;
+        numerator_accum = numerator_accum * numerator / (denominator * i);
+        i += U256::from(1);
+    }
+    output / denominator
+}
+
+/// Maximum number of blocks to project the base fee forward before giving up
+/// and treating a base-fee-too-low transaction as sustained exclusion rather
+/// than transient pricing.
+const MAX_BASE_FEE_PROJECTION_BLOCKS: usize = 256;
+
+/// Apply the EIP-1559 base fee update rule for one block, assuming it
+/// consumes `gas_used` out of `gas_limit`.
+fn next_base_fee(base_fee: U256, gas_used: U256, gas_limit: U256) -> U256 {
+    let target = gas_limit / 2;
+    if gas_used > target {
+        let delta = std::cmp::max(base_fee * (gas_used - target) / target / 8, U256::one());
+        base_fee + delta
+    } else {
+        let delta = base_fee * (target - gas_used) / target / 8;
+        base_fee - delta
+    }
+}
+
+/// Project how many blocks it would take for `max_base_fee` to become
+/// viable, assuming every subsequent block is empty (the fastest the base
+/// fee can fall). Returns 0 if it is already viable, and
+/// `MAX_BASE_FEE_PROJECTION_BLOCKS` if it isn't projected to become viable
+/// within that many blocks.
+fn project_blocks_until_base_fee_viable(
+    exec: &ExecutionPayload<Transaction>,
+    max_base_fee: U256,
+) -> usize {
+    let gas_limit = U256::from(exec.gas_limit.as_u64());
+    let mut base_fee = exec.base_fee_per_gas;
+    for blocks in 0..=MAX_BASE_FEE_PROJECTION_BLOCKS {
+        if max_base_fee >= base_fee {
+            return blocks;
+        }
+        base_fee = next_base_fee(base_fee, U256::zero(), gas_limit);
+    }
+    MAX_BASE_FEE_PROJECTION_BLOCKS
+}
+
+/// Check if the transaction doesn't pay a high enough tip to have displaced a
+/// transaction the block did include. A transaction only genuinely missed
+/// out on its tip if it falls below *both* the block's own marginal
+/// inclusion threshold and the one implied by the pool the monitor actually
+/// observed, and doesn't fit in whatever gas room was left unused under
+/// either reconstruction. Requiring both avoids flagging a transaction as
+/// tip-too-low solely because the pool-based reconstruction missed gas used
+/// by a transaction the monitor's nodes never observed (e.g. private order
+/// flow), or vice versa.
 fn check_tip_too_low(
     transaction: &Transaction,
     exec: &ExecutionPayload<Transaction>,
+    inclusion_threshold: &InclusionThreshold,
+    pool_inclusion_threshold: &InclusionThreshold,
 ) -> Result<bool, TransactionError> {
-    let min_tip = get_min_tip(&exec.transactions, exec.base_fee_per_gas);
-    Ok(get_tip(transaction, exec.base_fee_per_gas)? < min_tip)
+    let tip = get_tip(transaction, exec.base_fee_per_gas)?;
+    let too_low_for_block = tip < inclusion_threshold.tip && transaction.gas > inclusion_threshold.free_gas;
+    let too_low_for_pool =
+        tip < pool_inclusion_threshold.tip && transaction.gas > pool_inclusion_threshold.free_gas;
+    Ok(too_low_for_block && too_low_for_pool)
+}
+
+/// The marginal tip and leftover gas implied by greedily packing a set of
+/// transactions (the block's own, or the pool's eligible candidates) by
+/// descending effective tip. `tip` is the effective tip of the last
+/// transaction that still fit before `gas_limit` was reached (the lowest tip
+/// the block was willing to include), and `free_gas` is whatever gas room
+/// remained unused below `gas_limit` once that greedy packing stopped.
+#[derive(Debug, Clone, Copy)]
+pub struct InclusionThreshold {
+    pub tip: U256,
+    pub free_gas: U256,
+}
+
+/// Greedily pack `tips_and_gas`, sorted by descending tip, into `gas_limit`,
+/// and return the marginal inclusion threshold this implies: `tip` is the
+/// effective tip of the last transaction that still fit, and `free_gas` is
+/// whatever gas room remained unused once packing stopped.
+fn greedy_inclusion_threshold(mut tips_and_gas: Vec<(U256, U256)>, gas_limit: U256) -> InclusionThreshold {
+    tips_and_gas.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let mut gas_used = U256::zero();
+    let mut tip = U256::MAX;
+    for (tx_tip, tx_gas) in tips_and_gas {
+        if gas_used + tx_gas > gas_limit {
+            break;
+        }
+        gas_used += tx_gas;
+        tip = tx_tip;
+    }
+    InclusionThreshold {
+        tip,
+        free_gas: gas_limit - gas_used,
+    }
+}
+
+/// Reconstruct the block's marginal inclusion threshold, as if its
+/// transactions had been greedily packed by descending effective tip.
+fn get_inclusion_threshold(exec: &ExecutionPayload<Transaction>) -> InclusionThreshold {
+    let tips_and_gas = exec
+        .transactions
+        .iter()
+        .filter_map(|tx| get_tip(tx, exec.base_fee_per_gas).ok().map(|tip| (tip, tx.gas)))
+        .collect();
+    greedy_inclusion_threshold(tips_and_gas, U256::from(exec.gas_limit.as_u64()))
+}
+
+/// Reconstruct the marginal inclusion threshold from the monitor's own view
+/// of the pool rather than from the block's own selection: greedily pack the
+/// fee-ranked, economically eligible candidates the pool had pending at
+/// `timestamp` (see `Pool::eligible_at`) into the block's gas budget. Unlike
+/// `get_inclusion_threshold`, this isn't circular -- it doesn't assume the
+/// proposer's own selection was the best available one. `check_tip_too_low`
+/// requires a transaction to fall below both thresholds, so this one only
+/// narrows what the block-based threshold flags; it never flags a
+/// transaction on its own.
+fn get_pool_inclusion_threshold(
+    pool: &Pool,
+    timestamp: DateTime<Utc>,
+    exec: &ExecutionPayload<Transaction>,
+) -> InclusionThreshold {
+    let tips_and_gas = pool
+        .eligible_at(timestamp, exec.base_fee_per_gas)
+        .iter()
+        .filter_map(|tx| {
+            let transaction = tx.transaction.as_ref()?;
+            Some((get_tip(transaction, exec.base_fee_per_gas).ok()?, transaction.gas))
+        })
+        .collect();
+    greedy_inclusion_threshold(tips_and_gas, U256::from(exec.gas_limit.as_u64()))
 }
 
-/// Check if there is a mismatch between transaction and account nonce.
+/// Check if the transaction's nonce is lower than the account's current
+/// nonce (stale; an earlier transaction already consumed it) or higher (a
+/// gap; an earlier transaction from the same sender hasn't landed yet, so
+/// this one isn't ready for inclusion). Only an exact match is includable.
 async fn check_nonce_mismatch(
     transaction: &Transaction,
     beacon_block: &BeaconBlock<Transaction>,
     nonce_cache: &mut NonceCache,
-) -> Result<bool, NonceCacheError> {
+) -> Result<Option<NonInclusionReason>, NonceCacheError> {
     let nonce = nonce_cache.get(&transaction.from, beacon_block).await?;
-    Ok(nonce != transaction.nonce.as_u64())
+    let tx_nonce = transaction.nonce.as_u64();
+    if tx_nonce < nonce {
+        Ok(Some(NonInclusionReason::NonceTooLow))
+    } else if tx_nonce > nonce {
+        Ok(Some(NonInclusionReason::NonceInFuture))
+    } else {
+        Ok(None)
+    }
 }
 
-/// Get the minimum tip of the given transactions. Transactions with missing
-/// required fields are ignored. If there's no transactions to consider, returns
-/// the maximum of U256.
-fn get_min_tip(transactions: &[Transaction], base_fee: U256) -> U256 {
-    transactions
-        .iter()
-        .filter_map(|tx| get_tip(tx, base_fee).ok())
-        .min()
-        .unwrap_or(U256::MAX)
+/// How strongly a block's head was attested, used to judge whether honest
+/// proposers following proposer-boost re-org rules could (or should) have
+/// orphaned it instead of extending it.
+#[derive(Debug, Clone)]
+pub struct AttestationInfo {
+    /// Attesting weight the block received, in Gwei, per the consensus
+    /// node's fork choice dump.
+    pub weight_gwei: u64,
+    /// Weight below which a block is considered weakly attested.
+    pub threshold_gwei: u64,
+    /// Whether the block arrived after the slot's attestation deadline.
+    pub is_late: bool,
+}
+
+impl AttestationInfo {
+    /// A block is weakly attested if it received less attesting weight than
+    /// the configured threshold.
+    pub fn is_weakly_attested(&self) -> bool {
+        self.weight_gwei < self.threshold_gwei
+    }
+
+    /// A block was re-orgable if it was both late and weakly attested, i.e.
+    /// honest proposers following proposer-boost could have orphaned it.
+    pub fn is_reorgable(&self) -> bool {
+        self.is_late && self.is_weakly_attested()
+    }
+}
+
+/// A transaction confirmed missing from a block: it reached observation
+/// quorum, so its absence is real rather than a propagation artifact, and it
+/// carries the timing and fee data needed to persist it without recomputing
+/// from live state.
+#[derive(Debug, Clone)]
+pub struct MissedTransaction {
+    pub hash: TxHash,
+    pub transaction: Transaction,
+    pub first_seen: DateTime<Utc>,
+    pub quorum_reached: DateTime<Utc>,
+    pub tip: i64,
 }
 
 #[derive(Debug)]
 pub struct Analysis {
     pub beacon_block: BeaconBlock<Transaction>,
-    pub missing_transactions: HashMap<TxHash, ObservedTransaction>,
+    pub quorum: usize,
+    pub missing_transactions: HashMap<TxHash, MissedTransaction>,
     pub included_transactions: HashMap<TxHash, ObservedTransaction>,
     pub num_txs_in_block: usize,
     pub num_txs_in_pool: usize,
+    pub num_quorum_not_reached: usize,
     pub num_only_tx_hash: usize,
     pub num_replaced_txs: usize,
+    pub num_suspicious_replacements: usize,
+    pub num_nonce_blocked: usize,
     pub non_inclusion_reasons: HashMap<NonInclusionReason, usize>,
+    /// For each transaction classified as `BaseFeeTooLow`, the number of
+    /// blocks (assuming empty subsequent blocks) until its max base fee
+    /// would become viable. 0 would mean it's already viable now.
+    pub base_fee_projections: HashMap<TxHash, usize>,
+    pub attestation: Option<AttestationInfo>,
+    /// Which relay (if any) delivered this block's payload, and the builder
+    /// that built it. `None` means no configured relay reported delivering
+    /// it, which is also what a locally-built block looks like.
+    pub relay_provenance: Option<RelayProvenance>,
+    pub inclusion_threshold: InclusionThreshold,
     pub duration: Duration,
 }
 
 impl Analysis {
     pub fn summary(&self) -> String {
-        format!(
+        let mut s = format!(
             "Analysis for block {beacon_block}: {included} txs from pool included, {missing} \
-             missed, {in_pool} in pool, {in_block} in block, {only_hash} only hash known, \
-             {replaced} replaced, {nonce_mismatch} nonce mismatch, {not_enough_space} not enough \
-             space, {base_fee_too_low} base fee too low, {tip_too_low} tip too low, took \
-             {duration:.1}s",
+             missed, {in_pool} in pool, {in_block} in block, {quorum_not_reached} quorum not \
+             reached, {only_hash} only hash known, {replaced} replaced, {suspicious} suspicious \
+             replacements, {nonce_blocked} nonce blocked, {nonce_too_low} nonce too low, \
+             {nonce_in_future} nonce in future, {not_enough_space} not enough space, \
+             {not_enough_blob_space} not enough blob space, {base_fee_too_low} base fee too low \
+             ({viable_soon} viable within {cap} blocks), {blob_base_fee_too_low} blob base fee \
+             too low, {tip_too_low} tip too low (threshold {threshold_tip}, {free_gas} free \
+             gas), took {duration:.1}s",
             beacon_block = self.beacon_block,
             included = self.included_transactions.len(),
             missing = self.missing_transactions.len(),
             in_pool = self.num_txs_in_pool,
             in_block = self.num_txs_in_block,
+            quorum_not_reached = self.num_quorum_not_reached,
             only_hash = self.num_only_tx_hash,
             replaced = self.num_replaced_txs,
-            nonce_mismatch = self
+            suspicious = self.num_suspicious_replacements,
+            nonce_blocked = self.num_nonce_blocked,
+            nonce_too_low = self
                 .non_inclusion_reasons
-                .get(&NonInclusionReason::NonceMismatch)
+                .get(&NonInclusionReason::NonceTooLow)
+                .unwrap_or(&0),
+            nonce_in_future = self
+                .non_inclusion_reasons
+                .get(&NonInclusionReason::NonceInFuture)
                 .unwrap_or(&0),
             not_enough_space = self
                 .non_inclusion_reasons
                 .get(&NonInclusionReason::NotEnoughSpace)
                 .unwrap_or(&0),
+            not_enough_blob_space = self
+                .non_inclusion_reasons
+                .get(&NonInclusionReason::NotEnoughBlobSpace)
+                .unwrap_or(&0),
             base_fee_too_low = self
                 .non_inclusion_reasons
                 .get(&NonInclusionReason::BaseFeeTooLow)
                 .unwrap_or(&0),
+            blob_base_fee_too_low = self
+                .non_inclusion_reasons
+                .get(&NonInclusionReason::BlobBaseFeeTooLow)
+                .unwrap_or(&0),
+            viable_soon = self
+                .base_fee_projections
+                .values()
+                .filter(|&&blocks| blocks < MAX_BASE_FEE_PROJECTION_BLOCKS)
+                .count(),
+            cap = MAX_BASE_FEE_PROJECTION_BLOCKS,
             tip_too_low = self
                 .non_inclusion_reasons
                 .get(&NonInclusionReason::TipTooLow)
                 .unwrap_or(&0),
+            threshold_tip = self.inclusion_threshold.tip,
+            free_gas = self.inclusion_threshold.free_gas,
             duration = self.duration.as_secs_f64(),
-        )
+        );
+        if let Some(attestation) = &self.attestation {
+            s.push_str(
+                format!(
+                    ", {weight} Gwei attesting weight (reorgable: {reorgable})",
+                    weight = attestation.weight_gwei,
+                    reorgable = attestation.is_reorgable(),
+                )
+                .as_str(),
+            );
+        }
+        match &self.relay_provenance {
+            Some(provenance) => s.push_str(
+                format!(
+                    ", delivered by relay {} (builder {})",
+                    provenance.relay_url, provenance.builder_pubkey,
+                )
+                .as_str(),
+            ),
+            None => s.push_str(", no relay provenance"),
+        }
+        s
     }
 }
 
@@ -215,45 +605,143 @@ pub async fn analyze(
     beacon_block: &BeaconBlock<Transaction>,
     pool: &Pool,
     nonce_cache: &mut NonceCache,
+    quorum: usize,
+    attestation: Option<AttestationInfo>,
+    relay_provenance: Option<RelayProvenance>,
 ) -> Result<Analysis, NonceCacheError> {
     let start_time = Instant::now();
 
     let exec = &beacon_block.body.execution_payload;
     let txs_in_block: HashSet<&TxHash> =
         HashSet::from_iter(exec.transactions.iter().map(|tx| &tx.hash));
-    let senders_and_nonces_in_block: HashSet<(&Address, &U256)> =
-        HashSet::from_iter(exec.transactions.iter().map(|tx| (&tx.from, &tx.nonce)));
+    // Keyed by (sender, nonce) rather than sender alone: a sender having any
+    // transaction in the block doesn't mean a pool transaction of theirs at a
+    // different nonce was replaced, just that an earlier nonce went through
+    // while this one is still pending.
+    let in_block_by_sender_nonce: HashMap<(&Address, &U256), &Transaction> =
+        HashMap::from_iter(exec.transactions.iter().map(|tx| ((&tx.from, &tx.nonce), tx)));
+    let inclusion_threshold = get_inclusion_threshold(exec);
     let proposal_time = beacon_block.proposal_time();
+    let pool_inclusion_threshold = get_pool_inclusion_threshold(pool, proposal_time, exec);
     let pool_at_t = pool.content_at(proposal_time);
 
     let num_txs_in_block = exec.transactions.len();
     let num_txs_in_pool = pool_at_t.len();
 
     let mut included_txs = HashMap::new();
+    let mut num_quorum_not_reached = 0;
     let mut num_only_tx_hash = 0;
     let mut num_replaced_txs = 0;
+    let mut num_suspicious_replacements = 0;
+    let mut num_nonce_blocked = 0;
     let mut missing_txs = HashMap::new();
     let mut non_inclusion_reasons = HashMap::new();
+    let mut base_fee_projections = HashMap::new();
 
     for (hash, obs_tx) in pool_at_t {
         if txs_in_block.contains(&hash) {
             included_txs.insert(hash, obs_tx);
             continue;
         }
+        if obs_tx.num_nodes_seen(proposal_time) < quorum {
+            num_quorum_not_reached += 1;
+            metrics::QUORUM_NOT_REACHED_TRANSACTIONS.inc();
+            continue;
+        }
         if obs_tx.transaction.is_none() {
             num_only_tx_hash += 1;
             continue;
         }
         let tx = obs_tx.transaction.as_ref().unwrap();
-        if senders_and_nonces_in_block.contains(&(&tx.from, &tx.nonce)) {
-            num_replaced_txs += 1;
+        if obs_tx.is_nonce_blocked(pool) {
+            // A still-pending, earlier-nonce transaction from the same
+            // sender occupies the pool, so this transaction couldn't
+            // possibly have been included regardless of its own fee or
+            // age. Only the chain head is independently eligible; the rest
+            // of the package (see `Pool::package_for`) is judged via it.
+            let package = pool.package_for(hash);
+            if let Some(head) = package.first() {
+                log::debug!(
+                    "tx {} from {} is nonce-blocked behind {} in a package of {} txs",
+                    tx.hash,
+                    tx.from,
+                    head.hash,
+                    package.len(),
+                );
+            }
+            num_nonce_blocked += 1;
+            metrics::NONCE_BLOCKED_TRANSACTIONS.inc();
+            continue;
+        }
+        if let Some(&in_block_tx) = in_block_by_sender_nonce.get(&(&tx.from, &tx.nonce)) {
+            match is_legitimate_replacement(tx, in_block_tx, exec.base_fee_per_gas) {
+                Ok(true) => {
+                    num_replaced_txs += 1;
+                    metrics::REPLACED_TRANSACTIONS.inc();
+                }
+                Ok(false) => {
+                    num_suspicious_replacements += 1;
+                    metrics::SUSPICIOUS_REPLACEMENT_TRANSACTIONS.inc();
+                }
+                Err(e) => {
+                    log::warn!(
+                        "failed to check replacement fee bump for tx {}: {} (tx: {:?})",
+                        tx.hash,
+                        e,
+                        tx,
+                    );
+                    num_replaced_txs += 1;
+                    metrics::REPLACED_TRANSACTIONS.inc();
+                }
+            }
             continue;
         }
 
-        match check_inclusion(tx, beacon_block, nonce_cache).await {
-            Ok(Some(reason)) => *non_inclusion_reasons.entry(reason).or_insert(0) += 1,
+        match check_inclusion(
+            tx,
+            beacon_block,
+            nonce_cache,
+            &inclusion_threshold,
+            &pool_inclusion_threshold,
+        )
+        .await
+        {
+            Ok(Some(reason)) => {
+                if reason == NonInclusionReason::BaseFeeTooLow {
+                    if let Ok(max_base_fee) = get_max_base_fee(tx) {
+                        base_fee_projections
+                            .insert(hash, project_blocks_until_base_fee_viable(exec, max_base_fee));
+                    }
+                }
+                *non_inclusion_reasons.entry(reason).or_insert(0) += 1
+            }
             Ok(None) => {
-                missing_txs.insert(hash, obs_tx);
+                let first_seen = obs_tx
+                    .quorum_reached_timestamp(1)
+                    .expect("quorum has been reached");
+                let quorum_reached = obs_tx
+                    .quorum_reached_timestamp(quorum)
+                    .expect("quorum has been reached");
+                match get_tip(tx, exec.base_fee_per_gas) {
+                    Ok(tip) if tip <= U256::from(i64::MAX) => {
+                        missing_txs.insert(
+                            hash,
+                            MissedTransaction {
+                                hash: obs_tx.hash,
+                                transaction: tx.clone(),
+                                first_seen,
+                                quorum_reached,
+                                tip: tip.as_u64() as i64,
+                            },
+                        );
+                    }
+                    Ok(_) => log::warn!("ignoring missed tx {} with huge tip", tx.hash),
+                    Err(e) => log::error!(
+                        "missed tx {} whose tip we cannot determine: {}",
+                        tx.hash,
+                        e
+                    ),
+                }
             }
             Err(InclusionCheckError::TransactionError(e)) => {
                 log::warn!(
@@ -273,13 +761,21 @@ pub async fn analyze(
 
     Ok(Analysis {
         beacon_block: beacon_block.clone(),
+        quorum,
         missing_transactions: missing_txs,
         included_transactions: included_txs,
         num_txs_in_block,
         num_txs_in_pool,
+        num_quorum_not_reached,
         num_only_tx_hash,
         num_replaced_txs,
+        num_suspicious_replacements,
+        num_nonce_blocked,
         non_inclusion_reasons,
+        base_fee_projections,
+        attestation,
+        relay_provenance,
+        inclusion_threshold,
         duration,
     })
 }
@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use color_eyre::{eyre::WrapErr, Result};
+use crate::{
+    analyze::analyze,
+    cli::Config,
+    consensus_api::{BackfilledSlot, ConsensusProvider},
+    db,
+    nonce_cache::NonceCache,
+    pool::Pool,
+    types::BeaconBlock,
+};
+
+/// Execution-layer observations aren't available after the fact, so there's
+/// no real notion of "nodes that saw this transaction" to check a quorum
+/// against: every reconstructed candidate counts as observed by a single
+/// source.
+const BACKFILL_QUORUM: usize = 1;
+
+/// Upper bound on how many beacon block fetches are outstanding at once,
+/// passed to `fetch_beacon_blocks_in_range` as its `max_in_flight`.
+const MAX_IN_FLIGHT: usize = 8;
+
+/// Analyze every slot in `[start_slot, end_slot]` after the fact, e.g. to
+/// catch up on a gap left by downtime or to bootstrap a new deployment.
+/// There's no historical mempool to replay, so the candidate pool for a
+/// given slot is reconstructed from transactions that appear in any of the
+/// following `backfill_lookahead_slots` blocks: since we can't recover when
+/// they actually first appeared, they're assumed to have already been
+/// pending by the target slot's proposal time. `analyze`'s own nonce check
+/// still screens out any candidate that wasn't actually eligible yet (e.g.
+/// one that only became valid once an earlier same-sender transaction was
+/// included), so an overly generous candidate window just costs wasted work,
+/// not false positives.
+pub async fn backfill(config: &Config, start_slot: u64, end_slot: u64) -> Result<()> {
+    let consensus_provider = ConsensusProvider::new(config.consensus_http_url.clone());
+    let nonce_cache_provider = {
+        // Unwrapping is fine as try_from only fails with a parse error if url is
+        // invalid. Since we just serialized it, we know this is not the case.
+        ethers::providers::Provider::try_from(config.execution_http_url.as_str()).unwrap()
+    };
+    let mut nonce_cache = NonceCache::new(nonce_cache_provider, config.nonce_cache_size);
+
+    let retry_config = db::RetryConfig::from(config);
+    let storage = db::connect(config.db_connection.as_str(), retry_config)
+        .await
+        .wrap_err("failed to connect to db")?;
+    storage.create().await.wrap_err("failed to create db tables")?;
+
+    let fetch_end = end_slot + config.backfill_lookahead_slots;
+    log::info!(
+        "backfilling slots {}..={} (fetching up to slot {} for lookahead)",
+        start_slot,
+        end_slot,
+        fetch_end
+    );
+    let slots = consensus_provider
+        .fetch_beacon_blocks_in_range(start_slot, fetch_end, MAX_IN_FLIGHT)
+        .await
+        .wrap_err("failed to fetch beacon blocks for backfill range")?;
+
+    let mut beacon_blocks = BTreeMap::new();
+    for slot in slots {
+        if let BackfilledSlot::Block { slot, beacon_block, .. } = slot {
+            let root = consensus_provider
+                .fetch_beacon_block_root_by_slot(slot)
+                .await
+                .wrap_err_with(|| format!("failed to fetch beacon block root for slot {}", slot))?;
+            beacon_blocks.insert(slot, BeaconBlock::new(beacon_block, root));
+        }
+    }
+
+    let mut analyses = Vec::new();
+    for (&slot, beacon_block) in &beacon_blocks {
+        if slot > end_slot {
+            break;
+        }
+
+        let proposal_time = beacon_block.proposal_time();
+        let mut pending = BTreeMap::new();
+        for later_block in beacon_blocks
+            .range((slot + 1)..=(slot + config.backfill_lookahead_slots))
+            .map(|(_, b)| b)
+        {
+            for tx in &later_block.body.execution_payload.transactions {
+                pending
+                    .entry(tx.from)
+                    .or_insert_with(BTreeMap::new)
+                    .insert(tx.nonce.to_string(), tx.clone());
+            }
+        }
+        let mut pool = Pool::new(pending.values().map(|txs| txs.len()).sum());
+        pool.observe_pool(
+            0,
+            proposal_time,
+            ethers::types::TxpoolContent {
+                pending,
+                queued: BTreeMap::new(),
+            },
+        );
+
+        let analysis = analyze(
+            beacon_block,
+            &pool,
+            &mut nonce_cache,
+            BACKFILL_QUORUM,
+            None,
+            None,
+        )
+        .await;
+        match analysis {
+            Ok(analysis) => {
+                log::info!("{}", analysis.summary());
+                analyses.push(analysis);
+            }
+            Err(e) => log::error!("error analyzing backfilled block at slot {}: {}", slot, e),
+        }
+    }
+
+    storage
+        .insert_analyses(&analyses)
+        .await
+        .wrap_err("failed to persist backfilled analyses")?;
+    log::info!("backfill complete, persisted {} analyses", analyses.len());
+    Ok(())
+}
@@ -25,6 +25,13 @@ pub enum Commands {
     Run,
     /// Delete all data from the database
     TruncateDB,
+    /// Analyze a past slot range that was missed, e.g. due to downtime
+    Backfill {
+        /// First slot to analyze, inclusive
+        start_slot: u64,
+        /// Last slot to analyze, inclusive
+        end_slot: u64,
+    },
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -40,6 +47,150 @@ pub struct Config {
     pub db_enabled: bool,
     #[serde(default)]
     pub db_connection: String,
+
+    /// Attesting weight, in Gwei, below which a block is considered weakly
+    /// attested and thus re-orgable by honest proposers.
+    #[serde(default = "default_reorg_threshold_weight_gwei")]
+    pub reorg_threshold_weight_gwei: u64,
+
+    /// Maximum number of (block, account) nonce entries to keep cached.
+    #[serde(default = "default_nonce_cache_size")]
+    pub nonce_cache_size: usize,
+
+    /// Maximum number of transactions to keep tracked in the observed pool.
+    /// Once exceeded, the earliest-first-seen transactions are evicted,
+    /// skipping any still within `PRUNE_DELAY` of an in-flight analysis.
+    #[serde(default = "default_pool_max_size")]
+    pub pool_max_size: usize,
+
+    /// Approximate maximum memory footprint, in bytes, of the observed pool.
+    /// Once exceeded, already-disappeared transactions are evicted first,
+    /// then the oldest-seen remaining ones, regardless of `PRUNE_DELAY`.
+    #[serde(default = "default_pool_max_bytes")]
+    pub pool_max_bytes: usize,
+
+    /// Path to periodically snapshot the head history to, so it survives a
+    /// restart. If unset, no snapshot is taken and the monitor has to re-warm
+    /// from scratch.
+    #[serde(default)]
+    pub head_history_snapshot_path: Option<String>,
+
+    /// How often, in seconds, to write the head history snapshot.
+    #[serde(default = "default_head_history_snapshot_interval_seconds")]
+    pub head_history_snapshot_interval_seconds: u64,
+
+    /// If set, replay recorded `NewTransaction` events from this file instead
+    /// of connecting to a live node. Intended for regression tests over
+    /// captured censorship incidents and offline re-analysis.
+    #[serde(default)]
+    pub replay_path: Option<String>,
+
+    /// Initial delay, in milliseconds, before the first retry of a failed db
+    /// connect/create/insert. Doubles (times `db_retry_multiplier`) after
+    /// each subsequent attempt, with jitter.
+    #[serde(default = "default_db_retry_initial_interval_ms")]
+    pub db_retry_initial_interval_ms: u64,
+
+    /// Factor by which the retry delay grows after each failed attempt.
+    #[serde(default = "default_db_retry_multiplier")]
+    pub db_retry_multiplier: f64,
+
+    /// Total time, in seconds, to keep retrying a failed db
+    /// connect/create/insert before giving up.
+    #[serde(default = "default_db_retry_max_elapsed_seconds")]
+    pub db_retry_max_elapsed_seconds: u64,
+
+    /// Maximum number of analyses to buffer before flushing them to the db
+    /// in one batch, regardless of `db_flush_interval_seconds`.
+    #[serde(default = "default_db_flush_max_batch_size")]
+    pub db_flush_max_batch_size: usize,
+
+    /// Maximum time, in seconds, an analysis can sit in the buffer before
+    /// being flushed to the db, regardless of `db_flush_max_batch_size`.
+    #[serde(default = "default_db_flush_interval_seconds")]
+    pub db_flush_interval_seconds: u64,
+
+    /// MEV-Boost relay data API base URLs to query for payload provenance.
+    /// A slot with no block delivered by any of these relays is assumed to
+    /// be locally built rather than censored by a relay's block choice.
+    #[serde(default)]
+    pub relay_urls: Vec<url::Url>,
+
+    /// Maximum number of slots to keep cached relay provenance lookups for.
+    #[serde(default = "default_relay_cache_size")]
+    pub relay_cache_size: usize,
+
+    /// Additional execution HTTP URLs to query alongside `execution_http_url`
+    /// for txpool observations (unioned across nodes) and quorum-checked
+    /// block observations. The primary URL always participates; these are
+    /// the extra nodes.
+    #[serde(default)]
+    pub execution_http_quorum_urls: Vec<url::Url>,
+
+    /// Minimum number of execution nodes (out of 1 + the number of
+    /// `execution_http_quorum_urls`) that must agree on a canonical block for
+    /// it to be trusted. Txpool contents are not subject to this threshold.
+    #[serde(default = "default_execution_quorum_threshold")]
+    pub execution_quorum_threshold: usize,
+
+    /// When backfilling, how many slots past the target slot to look for
+    /// transactions that were plausibly pending at the target's proposal
+    /// time, since no historical mempool snapshot survives to consult
+    /// directly.
+    #[serde(default = "default_backfill_lookahead_slots")]
+    pub backfill_lookahead_slots: u64,
+}
+
+fn default_reorg_threshold_weight_gwei() -> u64 {
+    0
+}
+
+fn default_nonce_cache_size() -> usize {
+    1000
+}
+
+fn default_pool_max_size() -> usize {
+    100_000
+}
+
+fn default_pool_max_bytes() -> usize {
+    512 * 1024 * 1024
+}
+
+fn default_head_history_snapshot_interval_seconds() -> u64 {
+    300
+}
+
+fn default_db_retry_initial_interval_ms() -> u64 {
+    500
+}
+
+fn default_db_retry_multiplier() -> f64 {
+    2.0
+}
+
+fn default_db_retry_max_elapsed_seconds() -> u64 {
+    60
+}
+
+fn default_db_flush_max_batch_size() -> usize {
+    100
+}
+
+fn default_db_flush_interval_seconds() -> u64 {
+    5
+}
+
+fn default_relay_cache_size() -> usize {
+    1000
+}
+
+fn default_execution_quorum_threshold() -> usize {
+    1
+}
+
+fn default_backfill_lookahead_slots() -> u64 {
+    32
 }
 
 impl Config {
@@ -1,6 +1,7 @@
 use ethers::utils::keccak256;
+use futures::stream::{self, StreamExt};
 use rlp::Decodable;
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 use thiserror::Error;
 use url::Url;
 
@@ -23,6 +24,97 @@ pub struct ConsensusAPIResponse<T> {
     pub execution_optimistic: Option<bool>,
 }
 
+/// The part of `/eth/v1/beacon/headers/{slot}`'s response we care about:
+/// just the block root, since the header's other fields duplicate what's
+/// already in the beacon block itself.
+#[derive(Deserialize, Debug, Clone)]
+struct BeaconHeaderData {
+    root: H256,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct ForkChoiceNode {
+    block_root: H256,
+    weight: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct ForkChoiceDump {
+    fork_choice_nodes: Vec<ForkChoiceNode>,
+}
+
+/// A validator withdrawal, as introduced by the Capella fork.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Withdrawal {
+    #[serde(rename = "index", deserialize_with = "from_dec_str_u64")]
+    pub withdrawal_index: u64,
+    #[serde(deserialize_with = "from_dec_str_u64")]
+    pub validator_index: u64,
+    pub address: ethers::types::Address,
+    #[serde(deserialize_with = "from_dec_str_u64")]
+    pub amount: u64,
+}
+
+fn from_dec_str_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    s.parse().map_err(serde::de::Error::custom)
+}
+
+/// Just enough of the envelope and execution payload to determine the
+/// block's fork and, from Capella onwards, pull out its withdrawals,
+/// decoded separately from the strongly-typed `BeaconBlockWithoutRoot` so
+/// that adding a new fork's fields here doesn't require touching the
+/// execution-payload/transaction decoding path above.
+#[derive(Deserialize, Debug, Clone)]
+struct RawBeaconBlockEnvelope {
+    version: String,
+    data: RawSignedBeaconBlock,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct RawSignedBeaconBlock {
+    message: RawBeaconBlockMessage,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct RawBeaconBlockMessage {
+    body: RawBeaconBlockBody,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct RawBeaconBlockBody {
+    execution_payload: RawExecutionPayload,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+struct RawExecutionPayload {
+    #[serde(default)]
+    withdrawals: Option<Vec<Withdrawal>>,
+}
+
+/// Beacon block forks that don't yet have withdrawals.
+const FORKS_WITHOUT_WITHDRAWALS: &[&str] = &["phase0", "altair", "bellatrix"];
+/// Beacon block forks that carry withdrawals in their execution payload,
+/// following the Capella fork's introduction of partial withdrawals.
+const FORKS_WITH_WITHDRAWALS: &[&str] = &["capella", "deneb", "electra"];
+
+/// The outcome of fetching a single slot while backfilling a range, which
+/// may simply have been skipped (no block proposed).
+#[derive(Debug)]
+pub enum BackfilledSlot {
+    Block {
+        slot: u64,
+        beacon_block: BeaconBlockWithoutRoot<Transaction>,
+        withdrawals: Vec<Withdrawal>,
+    },
+    Missing {
+        slot: u64,
+    },
+}
+
 #[derive(Debug)]
 pub struct ConsensusProvider {
     http_url: Url,
@@ -36,7 +128,7 @@ impl ConsensusProvider {
     pub async fn fetch_beacon_block_by_root(
         &self,
         root: H256,
-    ) -> Result<BeaconBlockWithoutRoot<Transaction>, ConsensusAPIError> {
+    ) -> Result<(BeaconBlockWithoutRoot<Transaction>, Vec<Withdrawal>), ConsensusAPIError> {
         let path = format!("0x{}", hex::encode(root));
         self.fetch_beacon_block_with_path(path).await
     }
@@ -44,21 +136,91 @@ impl ConsensusProvider {
     pub async fn fetch_beacon_block_by_slot(
         &self,
         slot: u64,
-    ) -> Result<BeaconBlockWithoutRoot<Transaction>, ConsensusAPIError> {
+    ) -> Result<(BeaconBlockWithoutRoot<Transaction>, Vec<Withdrawal>), ConsensusAPIError> {
         let path = slot.to_string();
         self.fetch_beacon_block_with_path(path).await
     }
 
+    /// Backfill `[start_slot, end_slot]` with bounded concurrency (at most
+    /// `max_in_flight` requests outstanding at once), returning results in
+    /// slot order so they can be replayed straight into `History::append`
+    /// or the nonce cache. A slot with no proposed block is reported as
+    /// `BackfilledSlot::Missing` rather than aborting the whole backfill.
+    pub async fn fetch_beacon_blocks_in_range(
+        &self,
+        start_slot: u64,
+        end_slot: u64,
+        max_in_flight: usize,
+    ) -> Result<Vec<BackfilledSlot>, ConsensusAPIError> {
+        stream::iter(start_slot..=end_slot)
+            .map(|slot| async move {
+                match self.fetch_beacon_block_by_slot(slot).await {
+                    Ok((beacon_block, withdrawals)) => Ok(BackfilledSlot::Block {
+                        slot,
+                        beacon_block,
+                        withdrawals,
+                    }),
+                    Err(ConsensusAPIError::ReqwestError { source, .. })
+                        if source.status() == Some(reqwest::StatusCode::NOT_FOUND) =>
+                    {
+                        Ok(BackfilledSlot::Missing { slot })
+                    }
+                    Err(e) => Err(e),
+                }
+            })
+            .buffered(max_in_flight)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    /// Fetch the root of the beacon block proposed at `slot`, via the
+    /// lightweight `/eth/v1/beacon/headers/{slot}` endpoint. Used by
+    /// `backfill`, which only has a slot to go on (unlike the live head
+    /// event stream, which already carries the root).
+    pub async fn fetch_beacon_block_root_by_slot(&self, slot: u64) -> Result<H256, ConsensusAPIError> {
+        let url = self
+            .http_url
+            .join(format!("/eth/v1/beacon/headers/{}", slot).as_str())
+            .unwrap();
+        let r = reqwest::get(url)
+            .await
+            .map_err(|e| ConsensusAPIError::ReqwestError {
+                source: e,
+                requested: String::from("beacon block header"),
+            })?
+            .error_for_status()
+            .map_err(|e| ConsensusAPIError::ReqwestError {
+                source: e,
+                requested: String::from("beacon block header"),
+            })?;
+        let response: ConsensusAPIResponse<BeaconHeaderData> =
+            r.json()
+                .await
+                .map_err(|e| ConsensusAPIError::ReqwestError {
+                    source: e,
+                    requested: String::from("beacon block header"),
+                })?;
+        Ok(response.data.root)
+    }
+
+    /// Fetch and decode the beacon block at `path`, branching on the
+    /// response's `version` field to handle forks whose execution payload
+    /// shape differs (so far, only the presence of withdrawals from Capella
+    /// onwards) rather than assuming a single fixed payload shape. An
+    /// unrecognized version is treated as a genuine error rather than risking
+    /// a silent or panicking deserialize of a shape we don't understand yet.
     async fn fetch_beacon_block_with_path(
         &self,
         path: String,
-    ) -> Result<BeaconBlockWithoutRoot<Transaction>, ConsensusAPIError> {
+    ) -> Result<(BeaconBlockWithoutRoot<Transaction>, Vec<Withdrawal>), ConsensusAPIError> {
         let url = self
             .http_url
             .join(format!("/eth/v2/beacon/blocks/{}", path).as_str())
             .unwrap();
 
-        let r = reqwest::get(url)
+        let bytes = reqwest::get(url)
             .await
             .map_err(|e| ConsensusAPIError::ReqwestError {
                 source: e,
@@ -68,21 +230,44 @@ impl ConsensusProvider {
             .map_err(|e| ConsensusAPIError::ReqwestError {
                 source: e,
                 requested: String::from("beacon block"),
-            })?;
-        let response: ConsensusAPIResponse<SignedMessage<BeaconBlockWithoutRoot<String>>> = r
-            .json()
+            })?
+            .bytes()
             .await
             .map_err(|e| ConsensusAPIError::ReqwestError {
                 source: e,
                 requested: String::from("beacon block"),
             })?;
 
+        let response: ConsensusAPIResponse<SignedMessage<BeaconBlockWithoutRoot<String>>> =
+            serde_json::from_slice(&bytes).map_err(|e| ConsensusAPIError::UnexpectedResponse {
+                description: format!("error decoding beacon block: {}", e),
+            })?;
+
         if response.execution_optimistic.unwrap_or(false) {
             return Err(ConsensusAPIError::UnexpectedResponse {
                 description: String::from("consensus API response is optimistic"),
             });
         }
 
+        let raw: RawBeaconBlockEnvelope =
+            serde_json::from_slice(&bytes).map_err(|e| ConsensusAPIError::UnexpectedResponse {
+                description: format!("error decoding beacon block version: {}", e),
+            })?;
+        let withdrawals = if FORKS_WITHOUT_WITHDRAWALS.contains(&raw.version.as_str()) {
+            Vec::new()
+        } else if FORKS_WITH_WITHDRAWALS.contains(&raw.version.as_str()) {
+            raw.data
+                .message
+                .body
+                .execution_payload
+                .withdrawals
+                .unwrap_or_default()
+        } else {
+            return Err(ConsensusAPIError::UnexpectedResponse {
+                description: format!("unrecognized beacon block version {:?}", raw.version),
+            });
+        };
+
         let tx_strings = &response.data.message.body.execution_payload.transactions;
         let mut txs = Vec::new();
         for s in tx_strings {
@@ -107,7 +292,47 @@ impl ConsensusProvider {
             }
         }
         let beacon_block = BeaconBlockWithoutRoot::with_transactions(response.data.message, txs);
-        Ok(beacon_block)
+        Ok((beacon_block, withdrawals))
+    }
+
+    /// Fetch the fork choice weight (in Gwei) attesters have assigned to the
+    /// block with the given root, via the standard `/eth/v1/debug/fork_choice`
+    /// endpoint. Note this is the raw attesting weight, not yet normalized by
+    /// the slot's total active balance, so callers comparing it against a
+    /// threshold should account for that themselves.
+    pub async fn fetch_fork_choice_weight(&self, root: H256) -> Result<u64, ConsensusAPIError> {
+        let url = self.http_url.join("/eth/v1/debug/fork_choice").unwrap();
+        let r = reqwest::get(url)
+            .await
+            .map_err(|e| ConsensusAPIError::ReqwestError {
+                source: e,
+                requested: String::from("fork choice dump"),
+            })?
+            .error_for_status()
+            .map_err(|e| ConsensusAPIError::ReqwestError {
+                source: e,
+                requested: String::from("fork choice dump"),
+            })?;
+        let dump: ForkChoiceDump = r
+            .json()
+            .await
+            .map_err(|e| ConsensusAPIError::ReqwestError {
+                source: e,
+                requested: String::from("fork choice dump"),
+            })?;
+
+        let node = dump
+            .fork_choice_nodes
+            .iter()
+            .find(|n| n.block_root == root)
+            .ok_or_else(|| ConsensusAPIError::UnexpectedResponse {
+                description: format!("block {:?} not found in fork choice dump", root),
+            })?;
+        node.weight
+            .parse()
+            .map_err(|_| ConsensusAPIError::UnexpectedResponse {
+                description: format!("invalid weight {:?} in fork choice dump", node.weight),
+            })
     }
 
     pub async fn fetch_sync_status(&self) -> Result<ConsensusSyncStatus, ConsensusAPIError> {
@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use backoff::exponential::{ExponentialBackoff, ExponentialBackoffBuilder};
+use thiserror::Error;
+
+use crate::{analyze::Analysis, cli::Config};
+
+mod postgres;
+mod sqlite;
+
+pub use postgres::PostgresStorage;
+pub use sqlite::SqliteStorage;
+
+/// Persists analyses of missed transactions. Implemented for Postgres (the
+/// production backend) and SQLite (a file-based backend for contributors and
+/// CI who don't want to stand up Postgres) so the rest of the code depends
+/// only on `dyn Storage`.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn create(&self) -> Result<(), sqlx::Error>;
+    async fn drop(&self) -> Result<(), sqlx::Error>;
+    async fn insert_analysis(&self, analysis: &Analysis) -> Result<(), sqlx::Error>;
+    /// Persist `analyses` as batched multi-row inserts rather than one
+    /// transaction per analysis, to cut round-trips during chain catch-up.
+    /// Rows that target the same beacon block or transaction are coalesced
+    /// before insertion, since `ON CONFLICT DO NOTHING` would otherwise just
+    /// discard the duplicates anyway.
+    async fn insert_analyses(&self, analyses: &[Analysis]) -> Result<(), sqlx::Error>;
+}
+
+/// Which `Storage` implementation to use, determined from the scheme of
+/// `db_connection` (e.g. `postgres://...` or `sqlite://...`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbBackend {
+    Postgres,
+    Sqlite,
+}
+
+impl DbBackend {
+    fn from_connection_string(s: &str) -> Result<Self, ConnectError> {
+        match s.split_once("://").map(|(scheme, _)| scheme) {
+            Some("postgres" | "postgresql") => Ok(DbBackend::Postgres),
+            Some("sqlite") => Ok(DbBackend::Sqlite),
+            _ => Err(ConnectError::UnknownBackend(s.to_string())),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ConnectError {
+    #[error("cannot determine db backend from connection string \"{0}\"")]
+    UnknownBackend(String),
+    #[error("error connecting to db")]
+    Db(#[from] sqlx::Error),
+}
+
+/// Connect to the backend named by `db_connection`'s scheme and return it as
+/// a `dyn Storage`, so callers don't need to know which concrete backend is
+/// in use.
+pub async fn connect(
+    db_connection: &str,
+    retry_config: RetryConfig,
+) -> Result<Box<dyn Storage>, ConnectError> {
+    match DbBackend::from_connection_string(db_connection)? {
+        DbBackend::Postgres => Ok(Box::new(
+            PostgresStorage::connect(db_connection, retry_config).await?,
+        )),
+        DbBackend::Sqlite => Ok(Box::new(
+            SqliteStorage::connect(db_connection, retry_config).await?,
+        )),
+    }
+}
+
+/// Backoff parameters for retrying transient database failures, sourced from
+/// `Config` so operators can tune them without a rebuild.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryConfig {
+    pub initial_interval: Duration,
+    pub multiplier: f64,
+    pub max_elapsed_time: Duration,
+}
+
+impl From<&Config> for RetryConfig {
+    fn from(config: &Config) -> Self {
+        RetryConfig {
+            initial_interval: Duration::from_millis(config.db_retry_initial_interval_ms),
+            multiplier: config.db_retry_multiplier,
+            max_elapsed_time: Duration::from_secs(config.db_retry_max_elapsed_seconds),
+        }
+    }
+}
+
+impl RetryConfig {
+    fn backoff(&self) -> ExponentialBackoff<backoff::SystemClock> {
+        ExponentialBackoffBuilder::new()
+            .with_initial_interval(self.initial_interval)
+            .with_multiplier(self.multiplier)
+            .with_max_elapsed_time(Some(self.max_elapsed_time))
+            .build()
+    }
+}
+
+/// Whether `e` is worth retrying rather than failing permanently: a refused,
+/// reset, or aborted connection, or the pool having timed out or been
+/// closed out from under us. Everything else (constraint violations, bad
+/// queries, auth failures, ...) is assumed to be permanent.
+fn is_transient(e: &sqlx::Error) -> bool {
+    use std::io::ErrorKind;
+    match e {
+        sqlx::Error::Io(ioe) => matches!(
+            ioe.kind(),
+            ErrorKind::ConnectionRefused | ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted
+        ),
+        sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed => true,
+        _ => false,
+    }
+}
+
+fn classify(e: sqlx::Error) -> backoff::Error<sqlx::Error> {
+    if is_transient(&e) {
+        log::warn!("transient database error, retrying: {}", e);
+        backoff::Error::transient(e)
+    } else {
+        backoff::Error::permanent(e)
+    }
+}
@@ -0,0 +1,373 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use sqlx::QueryBuilder;
+
+use super::{classify, RetryConfig, Storage};
+use crate::analyze::Analysis;
+
+/// Whether `e` is SQLite's "duplicate column name" error, i.e. an `ALTER
+/// TABLE ... ADD COLUMN` that lost a race against an already-migrated
+/// schema, which SQLite (unlike Postgres) has no `IF NOT EXISTS` to
+/// suppress.
+fn is_duplicate_column_error(e: &sqlx::Error) -> bool {
+    match e {
+        sqlx::Error::Database(db_err) => db_err.message().contains("duplicate column name"),
+        _ => false,
+    }
+}
+
+/// File-based `Storage` backend for contributors and CI who want to run the
+/// monitor without standing up Postgres. Schema mirrors `PostgresStorage`'s,
+/// adapted to SQLite's dynamic typing and `?`-style bind parameters.
+pub struct SqliteStorage {
+    pool: sqlx::Pool<sqlx::Sqlite>,
+    retry_config: RetryConfig,
+}
+
+impl SqliteStorage {
+    pub async fn connect(s: &str, retry_config: RetryConfig) -> Result<Self, sqlx::Error> {
+        let pool = backoff::future::retry(retry_config.backoff(), || async {
+            log::debug!("connecting to sqlite db at {}", s);
+            sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(5)
+                .connect(s)
+                .await
+                .map_err(classify)
+        })
+        .await?;
+        Ok(SqliteStorage { pool, retry_config })
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStorage {
+    async fn create(&self) -> Result<(), sqlx::Error> {
+        backoff::future::retry(self.retry_config.backoff(), || async {
+            log::debug!("creating db tables");
+            let queries = [
+                sqlx::query!(
+                    r#"
+                    CREATE TABLE IF NOT EXISTS transaction (
+                        hash TEXT PRIMARY KEY,
+                        sender TEXT NOT NULL,
+                        first_seen TEXT NOT NULL,
+                        quorum_reached TEXT NOT NULL
+                    );
+                    "#
+                ),
+                sqlx::query!(
+                    r#"
+                    CREATE TABLE IF NOT EXISTS beacon_block (
+                        root TEXT PRIMARY KEY,
+                        slot INTEGER NOT NULL,
+                        proposer_index INTEGER NOT NULL,
+                        block_number INTEGER NOT NULL,
+                        proposal_time TEXT NOT NULL,
+                        relay_url TEXT,
+                        builder_pubkey TEXT
+                    );
+                    "#
+                ),
+                sqlx::query!(
+                    r#"
+                    CREATE TABLE IF NOT EXISTS miss (
+                        transaction_hash TEXT,
+                        beacon_block_root TEXT,
+                        proposal_time TEXT NOT NULL,
+                        tip INTEGER NOT NULL,
+                        PRIMARY KEY (transaction_hash, beacon_block_root),
+                        FOREIGN KEY (transaction_hash) REFERENCES transaction (hash),
+                        FOREIGN KEY (beacon_block_root) REFERENCES beacon_block (root)
+                    );
+                    "#
+                ),
+            ];
+
+            let mut tx = self.pool.begin().await.map_err(classify)?;
+            for query in queries {
+                query.execute(&mut tx).await.map_err(classify)?;
+            }
+
+            // `CREATE TABLE IF NOT EXISTS` above is a no-op against tables an
+            // already-running deployment created before these columns
+            // existed, which would otherwise leave `transaction` and
+            // `beacon_block` missing columns the inserts below require.
+            // SQLite has no `ADD COLUMN IF NOT EXISTS`, so run the `ALTER
+            // TABLE`s unconditionally and treat "duplicate column name" (the
+            // column's already there) as success rather than an error.
+            let add_column_queries = [
+                sqlx::query!("ALTER TABLE transaction ADD COLUMN sender TEXT;"),
+                sqlx::query!("ALTER TABLE transaction ADD COLUMN first_seen TEXT;"),
+                sqlx::query!("ALTER TABLE transaction ADD COLUMN quorum_reached TEXT;"),
+                sqlx::query!("ALTER TABLE beacon_block ADD COLUMN slot INTEGER;"),
+                sqlx::query!("ALTER TABLE beacon_block ADD COLUMN proposer_index INTEGER;"),
+                sqlx::query!("ALTER TABLE beacon_block ADD COLUMN block_number INTEGER;"),
+                sqlx::query!("ALTER TABLE beacon_block ADD COLUMN proposal_time TEXT;"),
+                sqlx::query!("ALTER TABLE beacon_block ADD COLUMN relay_url TEXT;"),
+                sqlx::query!("ALTER TABLE beacon_block ADD COLUMN builder_pubkey TEXT;"),
+            ];
+            for query in add_column_queries {
+                match query.execute(&mut tx).await {
+                    Ok(_) => {}
+                    Err(e) if is_duplicate_column_error(&e) => {}
+                    Err(e) => return Err(classify(e)),
+                }
+            }
+
+            tx.commit().await.map_err(classify)?;
+            log::debug!("db tables created");
+            Ok(())
+        })
+        .await
+    }
+
+    async fn drop(&self) -> Result<(), sqlx::Error> {
+        log::debug!("dropping db tables");
+        let queries = [
+            sqlx::query!(
+                r#"
+                DROP TABLE IF EXISTS miss;
+                "#
+            ),
+            sqlx::query!(
+                r#"
+                DROP TABLE IF EXISTS transaction;
+                "#
+            ),
+            sqlx::query!(
+                r#"
+                DROP TABLE IF EXISTS beacon_block;
+                "#
+            ),
+        ];
+        let mut tx = self.pool.begin().await?;
+        for query in queries {
+            query.execute(&mut tx).await?;
+        }
+        tx.commit().await?;
+        log::debug!("db tables dropped");
+        Ok(())
+    }
+
+    async fn insert_analysis(&self, analysis: &Analysis) -> Result<(), sqlx::Error> {
+        backoff::future::retry(self.retry_config.backoff(), || async {
+            log::debug!("persisting analysis for block {}", analysis.beacon_block);
+
+            let mut tx = self.pool.begin().await.map_err(classify)?;
+            let block = &analysis.beacon_block;
+            let beacon_root_str = &block.root.to_string();
+            let proposal_time = block.proposal_time().naive_utc();
+            let relay_url_str = analysis.relay_provenance.as_ref().map(|p| p.relay_url.to_string());
+            let builder_pubkey = analysis.relay_provenance.as_ref().map(|p| p.builder_pubkey.clone());
+            sqlx::query!(
+                r#"
+                INSERT INTO beacon_block (
+                    root,
+                    slot,
+                    proposer_index,
+                    block_number,
+                    proposal_time,
+                    relay_url,
+                    builder_pubkey
+                ) VALUES (
+                    ?,
+                    ?,
+                    ?,
+                    ?,
+                    ?,
+                    ?,
+                    ?
+                ) ON CONFLICT DO NOTHING;
+                "#,
+                beacon_root_str,
+                block.slot.as_u64() as i64,
+                block.proposer_index.as_u64() as i64,
+                block.body.execution_payload.block_number.as_u64() as i64,
+                proposal_time,
+                relay_url_str,
+                builder_pubkey,
+            )
+            .execute(&mut tx)
+            .await
+            .map_err(classify)?;
+
+            // A block can be re-analyzed after a reorg orphans it (see
+            // `State::process_new_head_event`), and the corrected analysis
+            // must be able to retract a stale miss, not just add to an
+            // ever-growing set of them, so replace this block's misses
+            // outright rather than leaving the first analysis's rows in
+            // place.
+            sqlx::query!(
+                r#"
+                DELETE FROM miss WHERE beacon_block_root = ?;
+                "#,
+                beacon_root_str,
+            )
+            .execute(&mut tx)
+            .await
+            .map_err(classify)?;
+
+            for missing_transaction in analysis.missing_transactions.values() {
+                let transaction_hash_str = missing_transaction.hash.to_string();
+                let sender_str = missing_transaction.transaction.from.to_string();
+                let queries = [
+                    sqlx::query!(
+                        r#"
+                    INSERT INTO transaction (
+                        hash,
+                        sender,
+                        first_seen,
+                        quorum_reached
+                    ) VALUES (
+                        ?,
+                        ?,
+                        ?,
+                        ?
+                    ) ON CONFLICT DO NOTHING;
+                    "#,
+                        transaction_hash_str,
+                        sender_str,
+                        missing_transaction.first_seen.naive_utc(),
+                        missing_transaction.quorum_reached.naive_utc(),
+                    ),
+                    sqlx::query!(
+                        r#"
+                    INSERT INTO miss (
+                        transaction_hash,
+                        beacon_block_root,
+                        proposal_time,
+                        tip
+                    ) VALUES (
+                        ?,
+                        ?,
+                        ?,
+                        ?
+                    ) ON CONFLICT DO NOTHING;
+                    "#,
+                        transaction_hash_str,
+                        beacon_root_str,
+                        proposal_time,
+                        missing_transaction.tip,
+                    ),
+                ];
+                for query in queries {
+                    query.execute(&mut tx).await.map_err(classify)?;
+                }
+            }
+            tx.commit().await.map_err(classify)?;
+            log::debug!("persisted analysis in db");
+            Ok(())
+        })
+        .await
+    }
+
+    async fn insert_analyses(&self, analyses: &[Analysis]) -> Result<(), sqlx::Error> {
+        if analyses.is_empty() {
+            return Ok(());
+        }
+
+        backoff::future::retry(self.retry_config.backoff(), || async {
+            log::debug!("persisting a batch of {} analyses", analyses.len());
+
+            let mut blocks = HashMap::new();
+            let mut transactions = HashMap::new();
+            let mut misses = Vec::new();
+            for analysis in analyses {
+                let block = &analysis.beacon_block;
+                let beacon_root_str = block.root.to_string();
+                let proposal_time = block.proposal_time().naive_utc();
+                let relay_url_str = analysis.relay_provenance.as_ref().map(|p| p.relay_url.to_string());
+                let builder_pubkey = analysis.relay_provenance.as_ref().map(|p| p.builder_pubkey.clone());
+                blocks.entry(beacon_root_str.clone()).or_insert_with(|| {
+                    (
+                        beacon_root_str.clone(),
+                        block.slot.as_u64() as i64,
+                        block.proposer_index.as_u64() as i64,
+                        block.body.execution_payload.block_number.as_u64() as i64,
+                        proposal_time,
+                        relay_url_str,
+                        builder_pubkey,
+                    )
+                });
+
+                for missing_transaction in analysis.missing_transactions.values() {
+                    let transaction_hash_str = missing_transaction.hash.to_string();
+                    transactions
+                        .entry(transaction_hash_str.clone())
+                        .or_insert_with(|| {
+                            (
+                                transaction_hash_str.clone(),
+                                missing_transaction.transaction.from.to_string(),
+                                missing_transaction.first_seen.naive_utc(),
+                                missing_transaction.quorum_reached.naive_utc(),
+                            )
+                        });
+                    misses.push((
+                        transaction_hash_str,
+                        beacon_root_str.clone(),
+                        proposal_time,
+                        missing_transaction.tip,
+                    ));
+                }
+            }
+
+            let mut tx = self.pool.begin().await.map_err(classify)?;
+
+            if !blocks.is_empty() {
+                QueryBuilder::new(
+                    "INSERT INTO beacon_block (root, slot, proposer_index, block_number, \
+                     proposal_time, relay_url, builder_pubkey) ",
+                )
+                .push_values(blocks.values(), |mut b, row| {
+                    b.push_bind(&row.0)
+                        .push_bind(row.1)
+                        .push_bind(row.2)
+                        .push_bind(row.3)
+                        .push_bind(row.4)
+                        .push_bind(&row.5)
+                        .push_bind(&row.6);
+                })
+                .push(" ON CONFLICT DO NOTHING")
+                .build()
+                .execute(&mut tx)
+                .await
+                .map_err(classify)?;
+            }
+
+            if !transactions.is_empty() {
+                QueryBuilder::new("INSERT INTO transaction (hash, sender, first_seen, quorum_reached) ")
+                    .push_values(transactions.values(), |mut b, row| {
+                        b.push_bind(&row.0)
+                            .push_bind(&row.1)
+                            .push_bind(row.2)
+                            .push_bind(row.3);
+                    })
+                    .push(" ON CONFLICT DO NOTHING")
+                    .build()
+                    .execute(&mut tx)
+                    .await
+                    .map_err(classify)?;
+            }
+
+            if !misses.is_empty() {
+                QueryBuilder::new(
+                    "INSERT INTO miss (transaction_hash, beacon_block_root, proposal_time, tip) ",
+                )
+                .push_values(&misses, |mut b, row| {
+                    b.push_bind(&row.0).push_bind(&row.1).push_bind(row.2).push_bind(row.3);
+                })
+                .push(" ON CONFLICT DO NOTHING")
+                .build()
+                .execute(&mut tx)
+                .await
+                .map_err(classify)?;
+            }
+
+            tx.commit().await.map_err(classify)?;
+            log::debug!("persisted a batch of {} analyses in db", analyses.len());
+            Ok(())
+        })
+        .await
+    }
+}
@@ -0,0 +1,102 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use thiserror::Error;
+use tokio::sync::mpsc::Sender;
+
+use crate::{
+    types::{NodeKey, TxHash},
+    watch::{watch, Event, NodeConfig, WatchError},
+};
+
+#[derive(Error, Debug)]
+pub enum EventSourceError {
+    #[error("error from live event source")]
+    Watch(#[from] WatchError),
+    #[error("error reading replay file")]
+    Io(#[from] std::io::Error),
+    #[error("error decoding a recorded event on line {line}")]
+    Decoding {
+        line: usize,
+        source: serde_json::Error,
+    },
+    #[error("failed to send event to channel")]
+    Send(#[from] tokio::sync::mpsc::error::SendError<Event>),
+}
+
+/// A source of `Event`s driving the monitor's classification logic. Separated
+/// out from `watch` (analogous to splitting a synchronous client from an
+/// asynchronous one) so `state::State` can be driven either from a live set
+/// of nodes or, deterministically, from a recorded incident.
+#[async_trait]
+pub trait EventSource: Send + Sync {
+    /// Run the source, sending `Event`s to `tx` until it is exhausted or an
+    /// error occurs.
+    async fn run(&self, tx: Sender<Event>) -> Result<(), EventSourceError>;
+}
+
+/// Drives events from a live set of execution and consensus nodes.
+pub struct LiveEventSource {
+    pub node_config: NodeConfig,
+}
+
+#[async_trait]
+impl EventSource for LiveEventSource {
+    async fn run(&self, tx: Sender<Event>) -> Result<(), EventSourceError> {
+        watch(&self.node_config, tx).await.map_err(EventSourceError::from)
+    }
+}
+
+/// A single recorded transaction sighting, as written to a replay file.
+#[derive(Debug, Deserialize)]
+struct RecordedTransaction {
+    node: NodeKey,
+    hash: TxHash,
+    timestamp: DateTime<Utc>,
+}
+
+/// Replays `NewTransaction` events recorded from a prior run. The file is
+/// newline-delimited JSON, one `RecordedTransaction` per line, ordered by
+/// timestamp. Events are emitted with the delays between their original
+/// timestamps preserved, so a consumer observes roughly the same pacing as
+/// during the recorded incident. This enables deterministic regression tests
+/// over captured censorship incidents and offline re-analysis with different
+/// parameters, without touching a real node.
+pub struct ReplayEventSource {
+    pub path: std::path::PathBuf,
+}
+
+#[async_trait]
+impl EventSource for ReplayEventSource {
+    async fn run(&self, tx: Sender<Event>) -> Result<(), EventSourceError> {
+        let content = tokio::fs::read_to_string(&self.path).await?;
+
+        let mut previous_timestamp = None;
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let recorded: RecordedTransaction =
+                serde_json::from_str(line).map_err(|source| EventSourceError::Decoding {
+                    line: i + 1,
+                    source,
+                })?;
+
+            if let Some(previous_timestamp) = previous_timestamp {
+                if let Ok(delay) = (recorded.timestamp - previous_timestamp).to_std() {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+            previous_timestamp = Some(recorded.timestamp);
+
+            tx.send(Event::NewTransaction {
+                node: recorded.node,
+                hash: recorded.hash,
+                timestamp: recorded.timestamp,
+            })
+            .await?;
+        }
+
+        Err(EventSourceError::Watch(WatchError::StreamEnded))
+    }
+}
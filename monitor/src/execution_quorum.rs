@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use ethers::{
+    providers::{Http, Middleware, Provider},
+    types::{Block, TxHash, TxpoolContent, H256},
+};
+use futures::stream::{self, StreamExt};
+
+use crate::{
+    metrics,
+    node_client::{self, NodeClient},
+};
+
+/// Upper bound on how many nodes are queried concurrently for a single
+/// observation, mirroring `consensus_api`'s bounded-concurrency fan-out.
+const MAX_CONCURRENT_QUERIES: usize = 8;
+
+/// Fans `txpool_content` and `get_block` queries out to every configured
+/// execution node and reconciles the results, so a single slow or diverging
+/// node can't silently corrupt the pool snapshot `analyze` relies on.
+pub struct QuorumExecutionProvider {
+    providers: Vec<Provider<Http>>,
+    node_clients: Vec<NodeClient>,
+    threshold: usize,
+}
+
+impl QuorumExecutionProvider {
+    /// Detects each node's client once up front, the same way `watch_heads`
+    /// already does for the primary node, since `txpool_content`'s shape (and
+    /// whether it's supported at all) differs by client.
+    pub async fn new(providers: Vec<Provider<Http>>, threshold: usize) -> Self {
+        let node_clients = stream::iter(&providers)
+            .then(|p| async move { node_client::detect(p).await.unwrap_or(NodeClient::Unknown) })
+            .collect()
+            .await;
+        QuorumExecutionProvider {
+            providers,
+            node_clients,
+            threshold,
+        }
+    }
+
+    /// Union the pending/queued transactions reported by every node that
+    /// supports `txpool_content`. A transaction only needs to be visible to
+    /// one node to be included here: the goal is to avoid missing
+    /// transactions a single node failed to relay, not to filter out ones
+    /// only some nodes saw. Returns `None` if no node returned any content.
+    pub async fn fetch_txpool_content(&self) -> Option<TxpoolContent> {
+        let contents: Vec<TxpoolContent> = stream::iter(self.providers.iter().zip(&self.node_clients))
+            .map(|(provider, client)| async move {
+                node_client::fetch_txpool_content(provider, *client)
+                    .await
+                    .unwrap_or_else(|e| {
+                        log::warn!("error fetching txpool content from execution node: {}", e);
+                        None
+                    })
+            })
+            .buffer_unordered(MAX_CONCURRENT_QUERIES)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+
+        if contents.is_empty() {
+            return None;
+        }
+
+        let mut pending: BTreeMap<_, BTreeMap<String, _>> = BTreeMap::new();
+        let mut queued: BTreeMap<_, BTreeMap<String, _>> = BTreeMap::new();
+        for content in contents {
+            for (account, txs) in content.pending {
+                pending.entry(account).or_default().extend(txs);
+            }
+            for (account, txs) in content.queued {
+                queued.entry(account).or_default().extend(txs);
+            }
+        }
+        Some(TxpoolContent { pending, queued })
+    }
+
+    /// Fetch the block at `hash` from every node and report it only if at
+    /// least `threshold` nodes agree it's canonical. A node that doesn't know
+    /// about the block (e.g. it's lagging or diverged onto a different fork)
+    /// counts against the quorum rather than being skipped, so a single slow
+    /// node shows up as a disagreement instead of going unnoticed.
+    pub async fn get_block_by_hash(&self, hash: H256) -> Option<Block<TxHash>> {
+        let blocks: Vec<Option<Block<TxHash>>> = stream::iter(&self.providers)
+            .map(|provider| async move {
+                provider.get_block(hash).await.unwrap_or_else(|e| {
+                    log::warn!("error fetching block {:#x} from execution node: {}", hash, e);
+                    None
+                })
+            })
+            .buffer_unordered(MAX_CONCURRENT_QUERIES)
+            .collect()
+            .await;
+
+        let mut agreeing = Vec::new();
+        for block in blocks.into_iter().flatten() {
+            agreeing.push(block);
+        }
+
+        let disagreeing = self.providers.len().saturating_sub(agreeing.len());
+        metrics::EXECUTION_QUORUM_DISAGREEING_NODES.set(disagreeing as i64);
+        if disagreeing > 0 {
+            log::warn!(
+                "{}/{} execution nodes did not agree on canonical block {:#x}",
+                disagreeing,
+                self.providers.len(),
+                hash
+            );
+        }
+
+        if agreeing.len() < self.threshold {
+            return None;
+        }
+        agreeing.into_iter().next()
+    }
+}
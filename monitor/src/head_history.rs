@@ -1,13 +1,24 @@
-use crate::types::{BeaconBlock, Timestamp};
+use crate::types::{BeaconBlock, Timestamp, H256};
 use ethers::types::Transaction;
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ObservedHead {
     pub head: BeaconBlock<Transaction>,
     pub timestamp: Timestamp,
 }
 
+/// A reorg was detected while observing a new head: `new_roots` replaced
+/// `orphaned_roots` as the canonical chain. `orphaned_roots` is ordered from
+/// most recently observed to least recent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReorgEvent {
+    pub depth: usize,
+    pub orphaned_roots: Vec<H256>,
+    pub new_roots: Vec<H256>,
+}
+
 #[derive(Debug)]
 pub struct HeadHistory(VecDeque<ObservedHead>);
 
@@ -17,8 +28,40 @@ impl HeadHistory {
         HeadHistory(VecDeque::new())
     }
 
+    /// Rebuild a history from a sequence of previously observed heads, e.g.
+    /// when restoring a snapshot taken with `observed_heads`. Heads are
+    /// re-inserted one by one via `insert` rather than trusted verbatim, so
+    /// the ordering invariant holds regardless of the order they are given
+    /// in.
+    pub fn from_observed_heads<I: IntoIterator<Item = ObservedHead>>(heads: I) -> Self {
+        let mut history = HeadHistory::new();
+        for oh in heads {
+            history.insert(oh);
+        }
+        history
+    }
+
+    /// Get a snapshot of the currently observed heads, ordered from oldest to
+    /// most recent.
+    pub fn observed_heads(&self) -> impl Iterator<Item = &ObservedHead> {
+        self.0.iter()
+    }
+
+    /// Insert an already-observed head into the history, keeping it ordered
+    /// by timestamp.
+    fn insert(&mut self, oh: ObservedHead) {
+        let i = self.0.partition_point(|o| o.timestamp <= oh.timestamp);
+        self.0.insert(i, oh);
+    }
+
     /// Insert a new block into the history observed at the given timestamp.
-    pub fn observe(&mut self, timestamp: Timestamp, head: BeaconBlock<Transaction>) {
+    /// Returns a `ReorgEvent` if the new head does not chain onto the most
+    /// recently observed head.
+    pub fn observe(
+        &mut self,
+        timestamp: Timestamp,
+        head: BeaconBlock<Transaction>,
+    ) -> Option<ReorgEvent> {
         let i = self.0.partition_point(|oh| oh.timestamp <= timestamp);
         log::debug!(
             "inserting block {} observed at time {} ({}s after proposal time) into head history at \
@@ -29,7 +72,52 @@ impl HeadHistory {
             i,
             self.0.len(),
         );
-        self.0.insert(i, ObservedHead { head, timestamp });
+
+        let reorg = self.detect_reorg(&head);
+
+        self.insert(ObservedHead { head, timestamp });
+        reorg
+    }
+
+    /// Check whether `head` chains onto the most recently observed head. If
+    /// not, walk back through the history to find the common ancestor and
+    /// report the orphaned and newly-canonical blocks.
+    fn detect_reorg(&self, head: &BeaconBlock<Transaction>) -> Option<ReorgEvent> {
+        let last = self.0.back()?;
+        if last.head.root == head.parent_root {
+            return None;
+        }
+
+        let mut orphaned_roots = Vec::new();
+        for oh in self.0.iter().rev() {
+            if oh.head.root == head.parent_root {
+                let depth = orphaned_roots.len();
+                log::warn!(
+                    "reorg detected: new head {} builds on {}, orphaning {} block(s)",
+                    head,
+                    head.parent_root,
+                    depth,
+                );
+                return Some(ReorgEvent {
+                    depth,
+                    orphaned_roots,
+                    new_roots: vec![head.root],
+                });
+            }
+            orphaned_roots.push(oh.head.root);
+        }
+
+        log::warn!(
+            "reorg detected but common ancestor {} lies outside the head history window, \
+             orphaning all {} known block(s)",
+            head.parent_root,
+            orphaned_roots.len(),
+        );
+        Some(ReorgEvent {
+            depth: orphaned_roots.len(),
+            orphaned_roots,
+            new_roots: vec![head.root],
+        })
     }
 
     /// Delete blocks that do not affect the history at or after cutoff.
@@ -60,6 +148,11 @@ impl HeadHistory {
         assert!(oh.timestamp <= timestamp);
         Some(oh.clone())
     }
+
+    /// Look up an observed head by its block root.
+    pub fn get_by_root(&self, root: H256) -> Option<BeaconBlock<Transaction>> {
+        self.0.iter().find(|oh| oh.head.root == root).map(|oh| oh.head.clone())
+    }
 }
 
 #[cfg(test)]
@@ -119,4 +212,51 @@ mod test {
         assert_eq!(h.at(30).unwrap(), o2);
         assert_eq!(h.at(300).unwrap(), o2);
     }
+
+    fn chained_block(slot: u64, parent_slot: u64) -> BeaconBlock<Transaction> {
+        let mut b = new_block(slot);
+        b.parent_root = H256::from_low_u64_be(parent_slot);
+        b
+    }
+
+    #[test]
+    fn test_no_reorg() {
+        let mut h = HeadHistory::new();
+        assert_eq!(h.observe(10, chained_block(10, 0)), None);
+        assert_eq!(h.observe(20, chained_block(20, 10)), None);
+    }
+
+    #[test]
+    fn test_shallow_reorg() {
+        let mut h = HeadHistory::new();
+        h.observe(10, chained_block(10, 0));
+        h.observe(20, chained_block(20, 10));
+
+        let reorg = h.observe(30, chained_block(21, 10));
+        assert_eq!(
+            reorg,
+            Some(ReorgEvent {
+                depth: 1,
+                orphaned_roots: vec![H256::from_low_u64_be(20)],
+                new_roots: vec![H256::from_low_u64_be(21)],
+            })
+        );
+    }
+
+    #[test]
+    fn test_reorg_beyond_window() {
+        let mut h = HeadHistory::new();
+        h.observe(10, chained_block(10, 0));
+        h.observe(20, chained_block(20, 10));
+
+        let reorg = h.observe(30, chained_block(31, 999));
+        assert_eq!(
+            reorg,
+            Some(ReorgEvent {
+                depth: 2,
+                orphaned_roots: vec![H256::from_low_u64_be(20), H256::from_low_u64_be(10)],
+                new_roots: vec![H256::from_low_u64_be(31)],
+            })
+        );
+    }
 }
@@ -1,17 +1,30 @@
+// Every file under src/ needs a matching `mod` line here to be compiled in
+// at all — `metrics.rs` sat unwired (and every metrics::* counter dead code)
+// for the whole stretch of commits between the fee-bump classifier landing
+// and the PoolEvent consumer fix, since nothing short of actually running
+// the binary surfaces a missing `mod` declaration.
 mod analyze;
+mod backfill;
 mod check_transaction;
 mod cli;
 mod compare_providers;
 mod consensus_api;
 mod db;
+mod event_source;
+mod execution_quorum;
 mod head_history;
+mod metrics;
+mod node_client;
 mod nonce_cache;
 mod pool;
+mod relay_api;
+mod snapshot;
 mod state;
 mod types;
 mod watch;
 
 use core::str::FromStr;
+use std::{path::PathBuf, time::Duration};
 
 use clap::Parser;
 use color_eyre::{
@@ -39,32 +52,52 @@ async fn main() -> Result<()> {
         cli::Commands::TruncateDB => truncate_db(config).await,
         cli::Commands::Check { txhash, n } => check(config, txhash, n).await,
         cli::Commands::CompareProviders => compare_providers(config).await,
+        cli::Commands::Backfill { start_slot, end_slot } => {
+            backfill::backfill(&config, start_slot, end_slot).await
+        }
     }
 }
 
 async fn run(config: cli::Config) -> Result<()> {
     let node_config = watch::NodeConfig::from(&config);
-    let mut state = state::State::new(&node_config);
+
+    let (pool_event_tx, mut pool_event_rx): (Sender<pool::PoolEvent>, Receiver<pool::PoolEvent>) =
+        mpsc::channel(100);
+    let mut state = state::State::new(&node_config, pool_event_tx);
 
     let (event_tx, mut event_rx): (Sender<watch::Event>, Receiver<watch::Event>) =
         mpsc::channel(100);
     let (analysis_tx, mut analysis_rx): (Sender<analyze::Analysis>, Receiver<analyze::Analysis>) =
         mpsc::channel(100);
 
-    node_config
-        .test_connection()
-        .await
-        .wrap_err("error connecting to Ethereum node")?;
-    log::info!("node connection is up");
+    let event_source: Box<dyn event_source::EventSource> = match &config.replay_path {
+        Some(path) => {
+            log::info!("replaying recorded events from {}", path);
+            Box::new(event_source::ReplayEventSource {
+                path: PathBuf::from(path),
+            })
+        }
+        None => {
+            node_config
+                .test_connection()
+                .await
+                .wrap_err("error connecting to Ethereum node")?;
+            log::info!("node connection is up");
+
+            if config.sync_check_enabled
+                && node_config
+                    .is_syncing()
+                    .await
+                    .wrap_err("error connecting to Ethereum node")?
+            {
+                return Err::<(), Report>(eyre!("node is still syncing"));
+            }
 
-    if config.sync_check_enabled
-        && node_config
-            .is_syncing()
-            .await
-            .wrap_err("error connecting to Ethereum node")?
-    {
-        return Err::<(), Report>(eyre!("node is still syncing"));
-    }
+            Box::new(event_source::LiveEventSource {
+                node_config: node_config.clone(),
+            })
+        }
+    };
 
     let process_handle = tokio::spawn(async move {
         while let Some(event) = event_rx.recv().await {
@@ -84,22 +117,44 @@ async fn run(config: cli::Config) -> Result<()> {
         }
         log::info!("spawning db task");
 
-        log::debug!("connecting to db at {}", config.db_connection);
-        let pool = db::connect(config.db_connection.as_str()).await?;
+        let retry_config = db::RetryConfig::from(&config);
 
-        db::migrate(&pool)
-            .await
-            .wrap_err("failed to apply db migrations")?;
-
-        while let Some(analysis) = analysis_rx.recv().await {
-            db::insert_analysis_into_db(&analysis, &pool)
-                .await
-                .wrap_err_with(|| {
-                    format!(
-                        "failed to insert analysis for block {} into db",
-                        analysis.beacon_block
-                    )
-                })?;
+        log::debug!("connecting to db at {}", config.db_connection);
+        let storage = db::connect(config.db_connection.as_str(), retry_config).await?;
+
+        storage.create().await.wrap_err("failed to create db tables")?;
+
+        // Buffer incoming analyses and flush them to the db in batches,
+        // whichever comes first: the buffer reaching `db_flush_max_batch_size`
+        // or `db_flush_interval_seconds` elapsing since the last flush. This
+        // cuts round-trips during chain catch-up compared to one transaction
+        // per analysis.
+        let mut buffer: Vec<analyze::Analysis> = Vec::new();
+        let mut flush_interval =
+            tokio::time::interval(Duration::from_secs(config.db_flush_interval_seconds));
+        flush_interval.tick().await;
+
+        loop {
+            tokio::select! {
+                analysis = analysis_rx.recv() => {
+                    match analysis {
+                        Some(analysis) => {
+                            buffer.push(analysis);
+                            if buffer.len() >= config.db_flush_max_batch_size {
+                                flush_buffer(storage.as_ref(), &mut buffer).await?;
+                                flush_interval.reset();
+                            }
+                        }
+                        None => {
+                            flush_buffer(storage.as_ref(), &mut buffer).await?;
+                            break;
+                        }
+                    }
+                }
+                _ = flush_interval.tick() => {
+                    flush_buffer(storage.as_ref(), &mut buffer).await?;
+                }
+            }
         }
 
         Err::<(), Report>(eyre!("db task ended unexpectedly"))
@@ -107,34 +162,57 @@ async fn run(config: cli::Config) -> Result<()> {
 
     let watch_handle = tokio::spawn(async move {
         log::info!("spawning watch task");
-        watch::watch(&node_config, event_tx)
+        event_source
+            .run(event_tx)
             .await
             .wrap_err("watch task failed")?;
         Err::<(), Report>(eyre!("watch task ended unexpectedly"))
     });
 
+    let pool_event_handle = tokio::spawn(async move {
+        while let Some(event) = pool_event_rx.recv().await {
+            match event {
+                pool::PoolEvent::FirstSeen { .. } => metrics::POOL_EVENT_FIRST_SEEN.inc(),
+                pool::PoolEvent::QuorumReached { .. } => metrics::POOL_EVENT_QUORUM_REACHED.inc(),
+                pool::PoolEvent::Disappeared { .. } => metrics::POOL_EVENT_DISAPPEARED.inc(),
+                pool::PoolEvent::Reappeared { .. } => metrics::POOL_EVENT_REAPPEARED.inc(),
+            }
+        }
+        Err::<(), Report>(eyre!("pool event task ended unexpectedly"))
+    });
+
     tokio::select! {
         r = process_handle => r,
         r = db_handle => r,
         r = watch_handle => r,
+        r = pool_event_handle => r,
     }??;
 
     Ok(())
 }
 
+/// Persist `buffer` to `storage` as one batch and clear it, if non-empty.
+async fn flush_buffer(storage: &dyn db::Storage, buffer: &mut Vec<analyze::Analysis>) -> Result<()> {
+    if buffer.is_empty() {
+        return Ok(());
+    }
+    storage
+        .insert_analyses(buffer)
+        .await
+        .wrap_err_with(|| format!("failed to insert a batch of {} analyses into db", buffer.len()))?;
+    buffer.clear();
+    Ok(())
+}
+
 async fn truncate_db(config: cli::Config) -> Result<()> {
+    let retry_config = db::RetryConfig::from(&config);
+
     log::info!("drop all data from db at {}", config.db_connection);
-    let pool = db::connect(config.db_connection.as_str())
+    let storage = db::connect(config.db_connection.as_str(), retry_config)
         .await
         .wrap_err("failed to connect to db")?;
 
-    db::migrate(&pool)
-        .await
-        .wrap_err("failed to apply db migrations")?;
-
-    db::truncate(&pool)
-        .await
-        .wrap_err("failed to drop db tables")?;
+    storage.drop().await.wrap_err("failed to drop db tables")?;
     Ok(())
 }
 
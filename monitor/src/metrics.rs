@@ -21,9 +21,31 @@ lazy_static! {
     .expect("can create metric");
     pub static ref BLOCKS: IntCounter =
         register_int_counter!("blocks", "Blocks").expect("can create metric");
+    pub static ref TXPOOL_UNSUPPORTED: IntCounterVec = register_int_counter_vec!(
+        "txpool_unsupported",
+        "Txpool observations skipped because no execution node returned txpool_content \
+         (unsupported client or request error)",
+        &["index"]
+    )
+    .expect("can create metric");
     pub static ref NONCE_CACHE_SIZE: IntGauge =
         register_int_gauge!(opts!("nonce_cache_size", "Nonce cache size"))
             .expect("can create metric");
+    pub static ref NONCE_CACHE_HITS: IntCounter =
+        register_int_counter!("nonce_cache_hits", "Nonce cache hits").expect("can create metric");
+    pub static ref NONCE_CACHE_MISSES: IntCounter =
+        register_int_counter!("nonce_cache_misses", "Nonce cache misses")
+            .expect("can create metric");
+    pub static ref NONCE_CACHE_EVICTIONS: IntCounter = register_int_counter!(
+        "nonce_cache_evictions",
+        "Nonce cache entries evicted for exceeding the cache's max size"
+    )
+    .expect("can create metric");
+    pub static ref POOL_EVICTIONS: IntCounter = register_int_counter!(
+        "pool_evictions",
+        "Pool transactions evicted for exceeding the pool's max size"
+    )
+    .expect("can create metric");
     pub static ref EVENT_CHANNEL_CAPACITY: Gauge =
         register_gauge!("event_channel_capacity", "Event channel capacity")
             .expect("can create metric");
@@ -63,6 +85,18 @@ lazy_static! {
         "Analyzed transactions that got replaced by other transactions of the same sender"
     )
     .expect("can create metric");
+    pub static ref NONCE_BLOCKED_TRANSACTIONS: IntCounter = register_int_counter!(
+        "nonce_blocked_transactions",
+        "Analyzed transactions that couldn't possibly have been included because a pending, \
+         earlier-nonce transaction from the same sender was still outstanding"
+    )
+    .expect("can create metric");
+    pub static ref SUSPICIOUS_REPLACEMENT_TRANSACTIONS: IntCounter = register_int_counter!(
+        "suspicious_replacement_transactions",
+        "Analyzed transactions that were displaced by a same-nonce transaction which did not \
+         meet the minimum fee bump"
+    )
+    .expect("can create metric");
     pub static ref NOT_ENOUGH_SPACE_TRANSACTIONS: IntCounter = register_int_counter!(
         "not_enough_space_transactions",
         "Analyzed transactions for which there wasn't enough space in the block"
@@ -78,9 +112,26 @@ lazy_static! {
         "Analyzed transactions whose tip was too small"
     )
     .expect("can create metric");
-    pub static ref NONCE_MISMATCH_TRANSACTIONS: IntCounter = register_int_counter!(
-        "nonce_mismatch_transactions",
-        "Analyzed transactions whose nonce was incorrect"
+    pub static ref NONCE_TOO_LOW_TRANSACTIONS: IntCounter = register_int_counter!(
+        "nonce_too_low_transactions",
+        "Analyzed transactions whose nonce was lower than the account's current nonce"
+    )
+    .expect("can create metric");
+    pub static ref NONCE_IN_FUTURE_TRANSACTIONS: IntCounter = register_int_counter!(
+        "nonce_in_future_transactions",
+        "Analyzed transactions whose nonce was higher than the account's current nonce, so an \
+         earlier transaction from the same sender hadn't landed yet"
+    )
+    .expect("can create metric");
+    pub static ref NOT_ENOUGH_BLOB_SPACE_TRANSACTIONS: IntCounter = register_int_counter!(
+        "not_enough_blob_space_transactions",
+        "Analyzed blob transactions for which there wasn't enough room left in the block's blob \
+         gas budget"
+    )
+    .expect("can create metric");
+    pub static ref BLOB_BASE_FEE_TOO_LOW_TRANSACTIONS: IntCounter = register_int_counter!(
+        "blob_base_fee_too_low_transactions",
+        "Analyzed blob transactions whose blob base fee was too low"
     )
     .expect("can create metric");
     pub static ref MISSING_TRANSACTIONS: IntCounter = register_int_counter!(
@@ -88,6 +139,52 @@ lazy_static! {
         "Analyzed transactions that should have been included but weren't"
     )
     .expect("can create metric");
+    pub static ref REORGS: IntCounter =
+        register_int_counter!("reorgs", "Reorgs detected in the head history").expect("can create metric");
+    pub static ref REORG_DEPTH: Gauge =
+        register_gauge!("reorg_depth", "Depth of the most recently detected reorg")
+            .expect("can create metric");
+    pub static ref ORPHANED_BLOCKS: IntCounter = register_int_counter!(
+        "orphaned_blocks",
+        "Blocks that were orphaned by a reorg"
+    )
+    .expect("can create metric");
+    pub static ref RELAY_CACHE_HITS: IntCounter =
+        register_int_counter!("relay_cache_hits", "Relay provenance cache hits")
+            .expect("can create metric");
+    pub static ref RELAY_CACHE_MISSES: IntCounter =
+        register_int_counter!("relay_cache_misses", "Relay provenance cache misses")
+            .expect("can create metric");
+    pub static ref RELAY_PROVENANCE_FOUND: IntCounter = register_int_counter!(
+        "relay_provenance_found",
+        "Blocks for which a configured relay reported delivering the payload"
+    )
+    .expect("can create metric");
+    pub static ref EXECUTION_QUORUM_DISAGREEING_NODES: IntGauge = register_int_gauge!(opts!(
+        "execution_quorum_disagreeing_nodes",
+        "Execution nodes that didn't agree on the canonical block in the most recent quorum check"
+    ))
+    .expect("can create metric");
+    pub static ref POOL_EVENT_FIRST_SEEN: IntCounter = register_int_counter!(
+        "pool_event_first_seen",
+        "Transactions first observed in the pool"
+    )
+    .expect("can create metric");
+    pub static ref POOL_EVENT_QUORUM_REACHED: IntCounter = register_int_counter!(
+        "pool_event_quorum_reached",
+        "Pool transactions that reached observation quorum"
+    )
+    .expect("can create metric");
+    pub static ref POOL_EVENT_DISAPPEARED: IntCounter = register_int_counter!(
+        "pool_event_disappeared",
+        "Pool transactions observed to disappear from the pool"
+    )
+    .expect("can create metric");
+    pub static ref POOL_EVENT_REAPPEARED: IntCounter = register_int_counter!(
+        "pool_event_reappeared",
+        "Pool transactions observed to reappear in the pool after disappearing"
+    )
+    .expect("can create metric");
 }
 
 pub async fn serve(config: &Config) {
@@ -0,0 +1,61 @@
+use ethers::providers::{Http, Middleware, Provider, ProviderError};
+
+use crate::types::TxpoolContent;
+
+/// Execution clients whose `txpool_content` shape (or lack thereof) is known
+/// to differ, detected from the `web3_clientVersion` string (e.g.
+/// "Geth/v1.13.0/linux-amd64/go1.21.0"), following the same detection
+/// approach as ethers' own `NodeClient`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeClient {
+    Geth,
+    Erigon,
+    OpenEthereum,
+    Nethermind,
+    Besu,
+    Unknown,
+}
+
+impl NodeClient {
+    /// Parse a `web3_clientVersion` string, taking the client name from the
+    /// segment before the first `/` as is conventional for Ethereum client
+    /// version strings.
+    fn parse(client_version: &str) -> Self {
+        let name = client_version.split('/').next().unwrap_or_default();
+        match name.to_ascii_lowercase().as_str() {
+            "geth" => NodeClient::Geth,
+            "erigon" => NodeClient::Erigon,
+            "openethereum" | "parity-ethereum" => NodeClient::OpenEthereum,
+            "nethermind" => NodeClient::Nethermind,
+            "besu" => NodeClient::Besu,
+            _ => NodeClient::Unknown,
+        }
+    }
+
+    /// Whether this client implements `txpool_content` at all, i.e. isn't
+    /// known to lack it entirely (as opposed to merely returning it in a
+    /// different shape).
+    fn supports_txpool_content(&self) -> bool {
+        !matches!(self, NodeClient::Besu)
+    }
+}
+
+/// Detect the execution client behind `provider` via `web3_clientVersion`.
+pub async fn detect(provider: &Provider<Http>) -> Result<NodeClient, ProviderError> {
+    let client_version = provider.client_version().await?;
+    Ok(NodeClient::parse(&client_version))
+}
+
+/// Fetch the pending/queued transaction pool, normalized across clients:
+/// clients known not to support `txpool_content` return `None` instead of
+/// propagating an RPC error, so callers can flag the observation as
+/// unavailable rather than treating it as a connection failure.
+pub async fn fetch_txpool_content(
+    provider: &Provider<Http>,
+    client: NodeClient,
+) -> Result<Option<TxpoolContent>, ProviderError> {
+    if !client.supports_txpool_content() {
+        return Ok(None);
+    }
+    Ok(Some(provider.txpool_content().await?))
+}
@@ -1,12 +1,26 @@
-use crate::types::{Address, BeaconBlock, H256};
-use ethers::providers::{Http, Middleware, Provider, ProviderError};
-use ethers::types::{BlockId, Transaction};
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Instant,
+};
+
+use crate::{
+    metrics,
+    types::{Address, BeaconBlock, H256},
+};
+use ethers::{
+    providers::{Http, Middleware, Provider, ProviderError},
+    types::{BlockId, Transaction},
+};
 use thiserror::Error;
 
+/// NonceCache holds nonces for every block it has analyzed, keyed by
+/// `(block_root, account)`, so that a backlog of queued blocks (or a
+/// re-queued block after a reorg) can be served without refetching accounts
+/// whose nonce is already known for that block.
 pub struct NonceCache {
-    beacon_block: BeaconBlock<Transaction>,
-    nonces: HashMap<Address, u64>,
+    nonces: HashMap<(H256, Address), u64>,
+    last_access_time: BTreeMap<(H256, Address), Instant>,
+    max_size: usize,
     provider: Provider<Http>,
 }
 
@@ -14,72 +28,107 @@ pub struct NonceCache {
 pub enum NonceCacheError {
     #[error("failed to fetch nonce")]
     ProviderError(#[from] ProviderError),
-    #[error("nonce cache is at block hash {internal}, but was queried at {queried}")]
-    WrongBlockError { internal: H256, queried: H256 },
 }
 
 impl NonceCache {
-    pub fn new(provider: Provider<Http>) -> Self {
-        NonceCache {
-            beacon_block: BeaconBlock::default(),
+    pub fn new(provider: Provider<Http>, max_size: usize) -> Self {
+        let c = NonceCache {
             nonces: HashMap::new(),
+            last_access_time: BTreeMap::new(),
+            max_size,
             provider,
-        }
+        };
+        c.report();
+        c
     }
 
+    /// Get the nonce of `account` at `beacon_block`, fetching it from the
+    /// execution provider on a cache miss.
     pub async fn get(
         &mut self,
         account: &Address,
         beacon_block: &BeaconBlock<Transaction>,
     ) -> Result<u64, NonceCacheError> {
-        if beacon_block.root != self.beacon_block.root {
-            return Err(NonceCacheError::WrongBlockError {
-                internal: self.beacon_block.root,
-                queried: beacon_block.root,
-            });
+        let key = (beacon_block.root, *account);
+        self.last_access_time.insert(key, Instant::now());
+
+        if let Some(&n) = self.nonces.get(&key) {
+            metrics::NONCE_CACHE_HITS.inc();
+            return Ok(n);
         }
+        metrics::NONCE_CACHE_MISSES.inc();
 
         let block_id = Some(BlockId::Hash(
             beacon_block.body.execution_payload.block_hash,
         ));
-        match self.nonces.get(account) {
-            Some(&n) => Ok(n),
-            None => {
-                let nonce_u256 = self
-                    .provider
-                    .get_transaction_count(account.clone(), block_id)
-                    .await
-                    .map_err(NonceCacheError::ProviderError)?;
-                let nonce = nonce_u256.as_u64();
-                self.nonces.insert(account.clone(), nonce);
-                Ok(nonce)
-            }
-        }
+        let nonce_u256 = self
+            .provider
+            .get_transaction_count(*account, block_id)
+            .await
+            .map_err(NonceCacheError::ProviderError)?;
+        let nonce = nonce_u256.as_u64();
+        self.nonces.insert(key, nonce);
+        self.prune();
+        self.report();
+        Ok(nonce)
     }
 
+    /// Register a newly analyzed block. The accounts already cached for the
+    /// block's parent are carried forward into the new block's layer (so a
+    /// backlog of queued blocks doesn't force a refetch for every account),
+    /// then updated with the nonces the block itself consumed. Unlike a
+    /// single-block cache, this never clears entries on a reorg: the
+    /// orphaned block's layer simply stays around until it's pruned like any
+    /// other block.
     pub fn apply_block(&mut self, beacon_block: BeaconBlock<Transaction>) {
-        if beacon_block.parent_root != self.beacon_block.root {
-            log::info!(
-                "clearing nonce cache due to reorg from {} to {}",
-                self.beacon_block,
-                beacon_block,
-            );
-            self.nonces.clear();
+        let root = beacon_block.root;
+        let parent_root = beacon_block.parent_root;
+
+        let parent_entries: Vec<(Address, u64)> = self
+            .nonces
+            .iter()
+            .filter(|((block_root, _), _)| *block_root == parent_root)
+            .map(|((_, account), &nonce)| (*account, nonce))
+            .collect();
+        for (account, nonce) in parent_entries {
+            self.nonces.entry((root, account)).or_insert(nonce);
         }
-        self.beacon_block = beacon_block;
 
         let mut num_modified = 0;
-        for tx in &self.beacon_block.body.execution_payload.transactions {
-            self.nonces.entry(tx.from).and_modify(|n| {
+        for tx in &beacon_block.body.execution_payload.transactions {
+            if let Some(n) = self.nonces.get_mut(&(root, tx.from)) {
                 *n = tx.nonce.as_u64() + 1;
                 num_modified += 1;
-            });
+            }
         }
+
+        self.prune();
+        self.report();
         log::debug!(
-            "applied block {} to nonce cache, updating {} of {} entries",
-            self.beacon_block,
+            "applied block {} to nonce cache, updating {} of {} entries for that block",
+            beacon_block,
             num_modified,
-            self.nonces.len(),
+            self.nonces.keys().filter(|(r, _)| *r == root).count(),
         );
     }
+
+    fn prune(&mut self) {
+        while self.nonces.len() > self.max_size {
+            if let Some((&key, _)) = self.last_access_time.iter().next() {
+                self.last_access_time.remove(&key);
+                self.nonces.remove(&key);
+                metrics::NONCE_CACHE_EVICTIONS.inc();
+            } else {
+                log::error!(
+                    "failed to prune nonce cache: last access time map is empty, but still too \
+                     many nonces"
+                );
+                break;
+            }
+        }
+    }
+
+    fn report(&self) {
+        metrics::NONCE_CACHE_SIZE.set(self.nonces.len() as i64);
+    }
 }
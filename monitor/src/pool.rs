@@ -1,9 +1,59 @@
 use std::{cmp::min, collections::HashMap};
 
 use chrono::{DateTime, Utc};
-use ethers::types::TxpoolContent;
+use ethers::types::{Address, TxpoolContent, U256};
+use tokio::sync::mpsc;
 
-use crate::types::{NodeKey, Transaction, TxHash};
+use crate::{
+    metrics,
+    types::{NodeKey, Transaction, TxHash},
+};
+
+/// Observation lifecycle transitions `Pool` notifies subscribers of, so
+/// downstream consumers (alerting, a metrics sink, a websocket feed) don't
+/// have to diff snapshots of pool state themselves.
+#[derive(Debug, Clone)]
+pub enum PoolEvent {
+    FirstSeen {
+        hash: TxHash,
+        node_key: NodeKey,
+        timestamp: DateTime<Utc>,
+    },
+    QuorumReached {
+        hash: TxHash,
+        quorum: usize,
+        timestamp: DateTime<Utc>,
+    },
+    Disappeared {
+        hash: TxHash,
+        timestamp: DateTime<Utc>,
+    },
+    Reappeared {
+        hash: TxHash,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+/// Get the effective priority fee a transaction would pay at the given base
+/// fee: `min(maxFeePerGas - baseFee, maxPriorityFeePerGas)` for EIP-1559 and
+/// later transaction types, or `gasPrice - baseFee` for legacy ones. Returns
+/// `None` if the transaction's type or required fields are missing, or if its
+/// max fee doesn't even cover the base fee.
+fn effective_tip(transaction: &Transaction, base_fee: U256) -> Option<U256> {
+    let t = transaction.transaction_type?.as_u64();
+    if t == 0 || t == 1 {
+        transaction.gas_price?.checked_sub(base_fee)
+    } else {
+        let max_fee_per_gas = transaction.max_fee_per_gas?;
+        let max_priority_fee_per_gas = transaction.max_priority_fee_per_gas?;
+        let tip = max_fee_per_gas.checked_sub(base_fee)?;
+        Some(min(tip, max_priority_fee_per_gas))
+    }
+}
+
+/// Approximate footprint, in bytes, of a hash-only observation: just the
+/// transaction hash without any of the other fields a full body would carry.
+const HASH_ONLY_SIZE_BYTES: usize = 32;
 
 /// ObservedTransaction stores a transaction hash and optionally a transaction
 /// body along with information about its observation history. For each node it
@@ -16,6 +66,12 @@ pub struct ObservedTransaction {
     pub transaction: Option<Transaction>,
     pub first_seen: HashMap<NodeKey, DateTime<Utc>>,
     pub disappeared: Option<DateTime<Utc>>,
+    /// Hash of the transaction that took over this one's `(sender, nonce)`
+    /// slot, if any. Set instead of relying on `disappeared` alone, so a
+    /// later disappearance can be attributed to being superseded by the
+    /// sender rather than unexplained censorship.
+    pub replaced_by: Option<TxHash>,
+    pub replaced_at: Option<DateTime<Utc>>,
 }
 
 impl ObservedTransaction {
@@ -26,6 +82,8 @@ impl ObservedTransaction {
             transaction: None,
             first_seen: HashMap::new(),
             disappeared: None,
+            replaced_by: None,
+            replaced_at: None,
         }
     }
 
@@ -74,28 +132,206 @@ impl ObservedTransaction {
     pub fn has_disappeared_before(&self, timestamp: DateTime<Utc>) -> bool {
         self.disappeared.map_or(false, |t| timestamp >= t)
     }
+
+    /// Check whether this transaction was superseded by another transaction
+    /// from the same sender at the same nonce, rather than simply dropped.
+    pub fn was_replaced(&self) -> bool {
+        self.replaced_by.is_some()
+    }
+
+    /// Mark this transaction as replaced by `replaced_by` at the given time,
+    /// keeping the earliest recorded replacement if called more than once.
+    fn mark_replaced_by(&mut self, replaced_by: TxHash, timestamp: DateTime<Utc>) {
+        self.replaced_by.get_or_insert(replaced_by);
+        let t = self.replaced_at.get_or_insert(timestamp);
+        *t = min(*t, timestamp);
+    }
+
+    /// Approximate the memory footprint of this entry: the RLP-encoded size
+    /// of the transaction body if known, or a fixed overhead for hash-only
+    /// observations.
+    pub fn size_bytes(&self) -> usize {
+        match &self.transaction {
+            Some(tx) => rlp::encode(tx).len(),
+            None => HASH_ONLY_SIZE_BYTES,
+        }
+    }
+
+    /// Whether a still-pending, earlier-nonce transaction from the same
+    /// sender occupies `pool`, meaning this transaction cannot possibly be
+    /// included yet regardless of its own fee or age. A transaction with an
+    /// unknown body (and thus unknown sender/nonce) is never considered
+    /// blocked, since it can't be placed in a nonce chain at all.
+    pub fn is_nonce_blocked(&self, pool: &Pool) -> bool {
+        let Some(tx) = self.transaction.as_ref() else {
+            return false;
+        };
+        let Some(prev_nonce) = tx.nonce.checked_sub(U256::from(1)) else {
+            return false;
+        };
+        let Some(prev_hash) = pool.by_sender_nonce.get(&(tx.from, prev_nonce)) else {
+            return false;
+        };
+        pool.txs
+            .get(prev_hash)
+            .map_or(false, |prev| prev.disappeared.is_none())
+    }
 }
 
-/// This struct keeps track of transactions we observed in the network.
+/// This struct keeps track of transactions we observed in the network,
+/// bounded to `max_size` entries so that a flood of gossiped hashes (e.g. via
+/// `observe_transaction`, which never corresponds to a real pool snapshot)
+/// can't grow it without limit.
 #[derive(Debug)]
-pub struct Pool(HashMap<TxHash, ObservedTransaction>);
+pub struct Pool {
+    txs: HashMap<TxHash, ObservedTransaction>,
+    max_size: usize,
+    /// Maps each sender/nonce pair with a known transaction body to the hash
+    /// of the transaction currently occupying that slot, so a later
+    /// transaction reusing it can be recognized as a replacement rather than
+    /// leaving the earlier one's disappearance unexplained.
+    by_sender_nonce: HashMap<(Address, U256), TxHash>,
+    /// Running total of `ObservedTransaction::size_bytes()` across `txs`,
+    /// kept in sync on every insertion and eviction so `size_bytes` doesn't
+    /// need to walk the whole map.
+    total_size: usize,
+    /// Number of nodes that must have observed a transaction for it to be
+    /// considered to have reached quorum. Only meaningful alongside
+    /// `event_tx`; see `with_events`.
+    quorum: usize,
+    /// Optional channel to notify of observation lifecycle transitions.
+    /// Sends are non-blocking (see `emit`) so a slow or absent consumer can
+    /// never stall the ingestion path.
+    event_tx: Option<mpsc::Sender<PoolEvent>>,
+}
 
 impl Pool {
-    /// Create a new empty pool.
-    pub fn new() -> Self {
-        Pool(HashMap::new())
+    /// Create a new empty pool that evicts down to at most `max_size`
+    /// entries whenever it is observed to exceed it.
+    pub fn new(max_size: usize) -> Self {
+        Pool {
+            txs: HashMap::new(),
+            max_size,
+            by_sender_nonce: HashMap::new(),
+            total_size: 0,
+            quorum: 0,
+            event_tx: None,
+        }
+    }
+
+    /// Configure the pool to notify `event_tx` of observation lifecycle
+    /// transitions, treating a transaction as having reached quorum once
+    /// `quorum` nodes have observed it.
+    pub fn with_events(mut self, quorum: usize, event_tx: mpsc::Sender<PoolEvent>) -> Self {
+        self.quorum = quorum;
+        self.event_tx = Some(event_tx);
+        self
+    }
+
+    /// Notify the configured event channel, if any, dropping (and logging)
+    /// the event instead of blocking if the channel is full or its receiver
+    /// has been dropped.
+    fn emit(&self, event: PoolEvent) {
+        let Some(event_tx) = &self.event_tx else {
+            return;
+        };
+        if let Err(e) = event_tx.try_send(event) {
+            log::warn!("dropping pool event: {}", e);
+        }
+    }
+
+    /// Number of transactions currently tracked by the pool.
+    pub fn len(&self) -> usize {
+        self.txs.len()
+    }
+
+    /// Whether the pool is tracking any transactions.
+    pub fn is_empty(&self) -> bool {
+        self.txs.is_empty()
+    }
+
+    /// Approximate total memory footprint of the pool, in bytes, as the sum
+    /// of `ObservedTransaction::size_bytes()` across all tracked entries.
+    pub fn size_bytes(&self) -> usize {
+        self.total_size
     }
 
     /// Get the transactions that have been observed at least once at or before
-    /// the given timestamp and have not disappeared yet.
+    /// the given timestamp, have not disappeared yet, and were not replaced by
+    /// a same-sender, same-nonce transaction.
     pub fn content_at(&self, timestamp: DateTime<Utc>) -> HashMap<TxHash, ObservedTransaction> {
-        self.0
+        self.txs
             .values()
-            .filter(|tx| tx.num_nodes_seen(timestamp) >= 1 && !tx.has_disappeared_before(timestamp))
+            .filter(|tx| {
+                tx.num_nodes_seen(timestamp) >= 1
+                    && !tx.has_disappeared_before(timestamp)
+                    && !tx.was_replaced()
+            })
             .map(|tx| (tx.hash, tx.clone()))
             .collect()
     }
 
+    /// Get the transactions that were pending at the given timestamp and
+    /// would have been economically eligible for inclusion in a block with
+    /// the given base fee, sorted by descending effective tip (see
+    /// `effective_tip`). Hash-only observations and replaced transactions are
+    /// excluded. The ordering is recomputed on every call rather than cached,
+    /// since the effective tip depends on `base_fee`, which varies from block
+    /// to block.
+    pub fn eligible_at(&self, timestamp: DateTime<Utc>, base_fee: U256) -> Vec<ObservedTransaction> {
+        let mut scored: Vec<(U256, ObservedTransaction)> = self
+            .txs
+            .values()
+            .filter(|tx| {
+                tx.num_nodes_seen(timestamp) >= 1
+                    && !tx.has_disappeared_before(timestamp)
+                    && !tx.was_replaced()
+            })
+            .filter_map(|tx| {
+                let tip = effective_tip(tx.transaction.as_ref()?, base_fee)?;
+                Some((tip, tx.clone()))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0));
+        scored.into_iter().map(|(_, tx)| tx).collect()
+    }
+
+    /// Return the contiguous chain of same-sender transactions, ordered from
+    /// the account's lowest pending nonce up to `hash`'s, analogous to
+    /// parity's package scoring: a descendant can never be included while an
+    /// ancestor at a lower nonce is still outstanding, so the chain should be
+    /// judged (and censorship-classified) as a unit rather than transaction
+    /// by transaction. A gap in the nonce sequence breaks the chain, and a
+    /// transaction with an unknown body yields an empty chain, since it
+    /// can't be placed by sender/nonce at all.
+    pub fn package_for(&self, hash: TxHash) -> Vec<ObservedTransaction> {
+        let Some(target) = self.txs.get(&hash) else {
+            return Vec::new();
+        };
+        let Some(target_tx) = target.transaction.as_ref() else {
+            return Vec::new();
+        };
+        let sender = target_tx.from;
+
+        let mut chain = vec![target.clone()];
+        let mut nonce = target_tx.nonce;
+        while let Some(prev_nonce) = nonce.checked_sub(U256::from(1)) {
+            let Some(prev_hash) = self.by_sender_nonce.get(&(sender, prev_nonce)) else {
+                break;
+            };
+            let Some(prev_obs) = self.txs.get(prev_hash) else {
+                break;
+            };
+            if prev_obs.disappeared.is_some() {
+                break;
+            }
+            chain.push(prev_obs.clone());
+            nonce = prev_nonce;
+        }
+        chain.reverse();
+        chain
+    }
+
     /// Insert a transaction into the pool observed on the given node at the
     /// given time.
     pub fn observe_transaction(
@@ -104,10 +340,33 @@ impl Pool {
         timestamp: DateTime<Utc>,
         hash: TxHash,
     ) {
-        self.0
+        let is_new = !self.txs.contains_key(&hash);
+        let obs_tx = self
+            .txs
             .entry(hash)
-            .or_insert_with(|| ObservedTransaction::new(hash))
-            .observe(node_key, timestamp);
+            .or_insert_with(|| ObservedTransaction::new(hash));
+        if is_new {
+            self.total_size += obs_tx.size_bytes();
+        }
+        let was_seen_by_node = obs_tx.first_seen.contains_key(&node_key);
+        let was_quorum_reached = self.quorum > 0 && obs_tx.first_seen.len() >= self.quorum;
+        obs_tx.observe(node_key, timestamp);
+        let now_quorum_reached = self.quorum > 0 && obs_tx.first_seen.len() >= self.quorum;
+
+        if !was_seen_by_node {
+            self.emit(PoolEvent::FirstSeen {
+                hash,
+                node_key,
+                timestamp,
+            });
+        }
+        if !was_quorum_reached && now_quorum_reached {
+            self.emit(PoolEvent::QuorumReached {
+                hash,
+                quorum: self.quorum,
+                timestamp,
+            });
+        }
     }
 
     /// Update the pool with a full snapshot of transactions in it taken on the
@@ -133,42 +392,94 @@ impl Pool {
         let mut num_new_objects = 0;
         let mut num_reappeared = 0;
         for (tx_hash, &tx) in &txs {
-            let obs_tx = self.0.entry(*tx_hash).or_insert_with(|| {
+            let is_new = !self.txs.contains_key(tx_hash);
+            let obs_tx = self.txs.entry(*tx_hash).or_insert_with(|| {
                 num_new += 1;
                 ObservedTransaction::new(*tx_hash)
             });
+            if is_new {
+                self.total_size += obs_tx.size_bytes();
+            }
             if obs_tx.transaction.is_none() {
+                let size_before = obs_tx.size_bytes();
                 num_new_objects += 1;
                 obs_tx.transaction = Some(tx.clone());
+                self.total_size += obs_tx.size_bytes() - size_before;
             }
-            if obs_tx.has_disappeared_before(timestamp) {
+            let reappeared = obs_tx.has_disappeared_before(timestamp);
+            if reappeared {
                 num_reappeared += 1;
                 obs_tx.clear_observations();
             }
+            let was_seen_by_node = obs_tx.first_seen.contains_key(&node_key);
+            let was_quorum_reached = self.quorum > 0 && obs_tx.first_seen.len() >= self.quorum;
             obs_tx.observe(node_key, timestamp);
+            let now_quorum_reached = self.quorum > 0 && obs_tx.first_seen.len() >= self.quorum;
+
+            if reappeared {
+                self.emit(PoolEvent::Reappeared {
+                    hash: *tx_hash,
+                    timestamp,
+                });
+            }
+            if !was_seen_by_node {
+                self.emit(PoolEvent::FirstSeen {
+                    hash: *tx_hash,
+                    node_key,
+                    timestamp,
+                });
+            }
+            if !was_quorum_reached && now_quorum_reached {
+                self.emit(PoolEvent::QuorumReached {
+                    hash: *tx_hash,
+                    quorum: self.quorum,
+                    timestamp,
+                });
+            }
         }
         let num_backfills = num_new_objects - num_new;
 
+        // mark transactions superseded by a same-sender, same-nonce transaction as replaced
+        let mut num_replaced = 0;
+        for (tx_hash, &tx) in &txs {
+            if let Some(prev_hash) = self.by_sender_nonce.insert((tx.from, tx.nonce), *tx_hash) {
+                if prev_hash != *tx_hash {
+                    if let Some(prev_obs) = self.txs.get_mut(&prev_hash) {
+                        if !prev_obs.was_replaced() {
+                            num_replaced += 1;
+                        }
+                        prev_obs.mark_replaced_by(*tx_hash, timestamp);
+                    }
+                }
+            }
+        }
+
         // mark transactions not in the pool as disappeared
         let mut num_disappeared = 0;
-        for (tx_hash, obs_tx) in self.0.iter_mut() {
+        let mut newly_disappeared = Vec::new();
+        for (tx_hash, obs_tx) in self.txs.iter_mut() {
             if !txs.contains_key(tx_hash) {
                 if !obs_tx.has_disappeared_before(timestamp) {
                     num_disappeared += 1;
+                    newly_disappeared.push(*tx_hash);
                 }
                 obs_tx.disappear_at(timestamp);
             }
         }
+        for hash in newly_disappeared {
+            self.emit(PoolEvent::Disappeared { hash, timestamp });
+        }
 
         log::debug!(
             "observed pool with {} txs ({} new, {} backfills, {} disappeared,
-{} reappeared, total size {})",
+{} reappeared, {} replaced, total size {})",
             num_txs,
             num_new,
             num_backfills,
             num_disappeared,
             num_reappeared,
-            self.0.len(),
+            num_replaced,
+            self.txs.len(),
         );
     }
 
@@ -176,10 +487,17 @@ impl Pool {
     /// timestamp.
     #[allow(dead_code)]
     pub fn prune(&mut self, cutoff: DateTime<Utc>) {
-        let len_before = self.0.len();
-        self.0
-            .retain(|_, obs_tx| !obs_tx.has_disappeared_before(cutoff));
-        let len_after = self.0.len();
+        let len_before = self.txs.len();
+        let mut removed_size = 0;
+        self.txs.retain(|_, obs_tx| {
+            let keep = !obs_tx.has_disappeared_before(cutoff);
+            if !keep {
+                removed_size += obs_tx.size_bytes();
+            }
+            keep
+        });
+        self.total_size -= removed_size;
+        let len_after = self.txs.len();
         log::debug!(
             "pruned pool from {} to {} by {} transactions",
             len_before,
@@ -187,6 +505,99 @@ impl Pool {
             len_before - len_after
         );
     }
+
+    /// Evict transactions until the pool's approximate byte size is at or
+    /// below `max_bytes`, following parity's `MemoryPool` eviction order:
+    /// already-disappeared transactions first (oldest `disappeared`
+    /// timestamp first), and only if that isn't enough, the remaining
+    /// entries (disappeared or not) oldest-`first_seen`-first.
+    pub fn prune_to_size(&mut self, max_bytes: usize) {
+        if self.total_size <= max_bytes {
+            return;
+        }
+
+        let mut num_evicted = 0;
+
+        let mut disappeared: Vec<(DateTime<Utc>, TxHash)> = self
+            .txs
+            .values()
+            .filter_map(|obs_tx| obs_tx.disappeared.map(|t| (t, obs_tx.hash)))
+            .collect();
+        disappeared.sort();
+        for (_, hash) in disappeared {
+            if self.total_size <= max_bytes {
+                break;
+            }
+            if let Some(obs_tx) = self.txs.remove(&hash) {
+                self.total_size -= obs_tx.size_bytes();
+                num_evicted += 1;
+            }
+        }
+
+        if self.total_size > max_bytes {
+            let mut by_first_seen: Vec<(DateTime<Utc>, TxHash)> = self
+                .txs
+                .values()
+                .filter_map(|obs_tx| obs_tx.first_seen.values().min().map(|t| (*t, obs_tx.hash)))
+                .collect();
+            by_first_seen.sort();
+            for (_, hash) in by_first_seen {
+                if self.total_size <= max_bytes {
+                    break;
+                }
+                if let Some(obs_tx) = self.txs.remove(&hash) {
+                    self.total_size -= obs_tx.size_bytes();
+                    num_evicted += 1;
+                }
+            }
+        }
+
+        log::debug!(
+            "pruned pool to size {} (max {}) by evicting {} transactions",
+            self.total_size,
+            max_bytes,
+            num_evicted
+        );
+    }
+
+    /// Evict the transactions with the earliest first sighting once the pool exceeds
+    /// `max_size`, skipping any transaction first seen at or after
+    /// `safe_cutoff` so we never evict one an in-flight analysis may still
+    /// need. Callers should pass the same cutoff used for `prune`.
+    pub fn evict(&mut self, safe_cutoff: DateTime<Utc>) {
+        if self.txs.len() <= self.max_size {
+            return;
+        }
+
+        let mut evictable: Vec<(DateTime<Utc>, TxHash)> = self
+            .txs
+            .values()
+            .filter_map(|obs_tx| {
+                let earliest_seen = obs_tx.first_seen.values().min()?;
+                (*earliest_seen < safe_cutoff).then_some((*earliest_seen, obs_tx.hash))
+            })
+            .collect();
+        evictable.sort();
+
+        let num_over = self.txs.len() - self.max_size;
+        let mut num_evicted = 0;
+        for (_, hash) in evictable.into_iter().take(num_over) {
+            if let Some(obs_tx) = self.txs.remove(&hash) {
+                self.total_size -= obs_tx.size_bytes();
+            }
+            num_evicted += 1;
+            metrics::POOL_EVICTIONS.inc();
+        }
+        if num_evicted < num_over {
+            log::warn!(
+                "pool exceeds max size {} by {} transactions, but only {} were outside the \
+                 safe cutoff to evict",
+                self.max_size,
+                num_over,
+                num_evicted
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -214,6 +625,10 @@ mod test {
         for (i, h) in hashes.iter().enumerate() {
             let mut tx = Transaction::default();
             tx.hash = *h;
+            tx.from = address;
+            // Each fixture transaction gets its own nonce so distinct hashes
+            // don't collide on the same (sender, nonce) replacement slot.
+            tx.nonce = U256::from(i);
             txs.insert(i.to_string(), tx);
         }
         pending.insert(address, txs);
@@ -313,7 +728,7 @@ mod test {
 
     #[test]
     fn test_observe_pool() {
-        let mut p = Pool::new();
+        let mut p = Pool::new(usize::MAX);
         p.observe_pool(0, t(10), make_pool(vec![H1, H2]));
         p.observe_pool(0, t(20), make_pool(vec![H1]));
 
@@ -333,9 +748,89 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_replacement() {
+        let address = Address::repeat_byte(0);
+        let make_nonce_pool = |hash_and_nonce: Vec<(TxHash, u64)>| -> TxpoolContent {
+            let queued = BTreeMap::new();
+            let mut pending = BTreeMap::new();
+            let mut txs = BTreeMap::new();
+            for (i, (h, nonce)) in hash_and_nonce.iter().enumerate() {
+                let mut tx = Transaction::default();
+                tx.hash = *h;
+                tx.from = address;
+                tx.nonce = U256::from(*nonce);
+                txs.insert(i.to_string(), tx);
+            }
+            pending.insert(address, txs);
+            TxpoolContent { pending, queued }
+        };
+
+        let mut p = Pool::new(usize::MAX);
+        p.observe_pool(0, t(10), make_nonce_pool(vec![(H1, 0)]));
+        // H2 reuses H1's nonce, so H1 was replaced rather than censored.
+        p.observe_pool(0, t(20), make_nonce_pool(vec![(H2, 0)]));
+
+        let content = p.content_at(t(20));
+        assert!(!content.contains_key(&H1));
+        assert!(content.contains_key(&H2));
+
+        let eligible = p.eligible_at(t(20), U256::zero());
+        assert!(!eligible.iter().any(|tx| tx.hash == H1));
+    }
+
+    #[test]
+    fn test_package_for() {
+        let address = Address::repeat_byte(0);
+        let make_nonce_pool = |hash_and_nonce: Vec<(TxHash, u64)>| -> TxpoolContent {
+            let queued = BTreeMap::new();
+            let mut pending = BTreeMap::new();
+            let mut txs = BTreeMap::new();
+            for (i, (h, nonce)) in hash_and_nonce.iter().enumerate() {
+                let mut tx = Transaction::default();
+                tx.hash = *h;
+                tx.from = address;
+                tx.nonce = U256::from(*nonce);
+                txs.insert(i.to_string(), tx);
+            }
+            pending.insert(address, txs);
+            TxpoolContent { pending, queued }
+        };
+
+        let h0 = TxHash::repeat_byte(10);
+        let h1 = TxHash::repeat_byte(11);
+        let h2 = TxHash::repeat_byte(12);
+        let h4 = TxHash::repeat_byte(14);
+
+        let mut p = Pool::new(usize::MAX);
+        // Nonce 3 is missing, so h4 (nonce 4) is separated from the
+        // contiguous 0..=2 chain by a gap.
+        p.observe_pool(
+            0,
+            t(10),
+            make_nonce_pool(vec![(h0, 0), (h1, 1), (h2, 2), (h4, 4)]),
+        );
+
+        let package = p.package_for(h2);
+        assert_eq!(
+            package.iter().map(|tx| tx.hash).collect::<Vec<_>>(),
+            vec![h0, h1, h2]
+        );
+
+        // h4's would-be ancestor at nonce 3 doesn't exist, so its package
+        // is just itself.
+        let package = p.package_for(h4);
+        assert_eq!(package.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![h4]);
+
+        let content = p.content_at(t(10));
+        assert!(content.get(&h2).unwrap().is_nonce_blocked(&p));
+        assert!(!content.get(&h0).unwrap().is_nonce_blocked(&p));
+        assert!(!content.get(&h4).unwrap().is_nonce_blocked(&p));
+    }
+
     #[test]
     fn test_observe_transaction() {
-        let mut p = Pool::new();
+        let mut p = Pool::new(usize::MAX);
         p.observe_transaction(0, t(10), H1);
         p.observe_transaction(1, t(11), H1);
         assert_content(
@@ -346,9 +841,42 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_eligible_at() {
+        let make_legacy_pool = |gas_prices: Vec<(TxHash, u64)>| -> TxpoolContent {
+            let queued = BTreeMap::new();
+            let mut pending = BTreeMap::new();
+            let mut txs = BTreeMap::new();
+            let address = Address::repeat_byte(0);
+            for (i, (h, gas_price)) in gas_prices.iter().enumerate() {
+                let mut tx = Transaction::default();
+                tx.hash = *h;
+                tx.from = address;
+                tx.nonce = U256::from(i);
+                tx.transaction_type = Some(0.into());
+                tx.gas_price = Some(U256::from(*gas_price));
+                txs.insert(i.to_string(), tx);
+            }
+            pending.insert(address, txs);
+            TxpoolContent { pending, queued }
+        };
+
+        let h3 = TxHash::repeat_byte(3);
+        let mut p = Pool::new(usize::MAX);
+        p.observe_pool(0, t(10), make_legacy_pool(vec![(H1, 100), (H2, 300)]));
+        // Hash-only observations have no known fee and must be excluded.
+        p.observe_transaction(0, t(10), h3);
+
+        let eligible = p.eligible_at(t(10), U256::from(50));
+        assert_eq!(
+            eligible.iter().map(|tx| tx.hash).collect::<Vec<_>>(),
+            vec![H2, H1]
+        );
+    }
+
     #[test]
     fn test_backfill() {
-        let mut p = Pool::new();
+        let mut p = Pool::new(usize::MAX);
         p.observe_transaction(0, t(10), H1);
         p.observe_pool(0, t(20), make_pool(vec![H1]));
         assert_content(
@@ -359,7 +887,7 @@ mod test {
 
     #[test]
     fn test_prune() {
-        let mut p = Pool::new();
+        let mut p = Pool::new(usize::MAX);
         p.observe_pool(0, t(10), make_pool(vec![H1, H2]));
         p.observe_pool(0, t(20), make_pool(vec![H1]));
         p.observe_pool(0, t(30), make_pool(vec![]));
@@ -377,4 +905,116 @@ mod test {
                 .collect(),
         );
     }
+
+    #[test]
+    fn test_events() {
+        let (tx, mut rx) = mpsc::channel(10);
+        let mut p = Pool::new(usize::MAX).with_events(2, tx);
+
+        p.observe_transaction(0, t(10), H1);
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            PoolEvent::FirstSeen {
+                hash: H1,
+                node_key: 0,
+                ..
+            }
+        ));
+        assert!(rx.try_recv().is_err());
+
+        // Reaching quorum (2 nodes) fires QuorumReached exactly once.
+        p.observe_transaction(1, t(11), H1);
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            PoolEvent::FirstSeen {
+                hash: H1,
+                node_key: 1,
+                ..
+            }
+        ));
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            PoolEvent::QuorumReached { hash: H1, quorum: 2, .. }
+        ));
+        assert!(rx.try_recv().is_err());
+
+        p.observe_transaction(1, t(12), H1);
+        assert!(rx.try_recv().is_err());
+
+        p.observe_pool(0, t(20), make_pool(vec![]));
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            PoolEvent::Disappeared { hash: H1, .. }
+        ));
+        assert!(rx.try_recv().is_err());
+
+        p.observe_pool(0, t(30), make_pool(vec![H1]));
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            PoolEvent::Reappeared { hash: H1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_size_bytes() {
+        let mut p = Pool::new(usize::MAX);
+        assert_eq!(p.size_bytes(), 0);
+        assert_eq!(p.len(), 0);
+        assert!(p.is_empty());
+
+        p.observe_transaction(0, t(10), H1);
+        assert_eq!(p.len(), 1);
+        assert_eq!(p.size_bytes(), HASH_ONLY_SIZE_BYTES);
+
+        // Backfilling a body grows the total size past the hash-only
+        // overhead, since the transaction's RLP encoding is now known.
+        p.observe_pool(0, t(20), make_pool(vec![H1]));
+        assert!(p.size_bytes() > HASH_ONLY_SIZE_BYTES);
+    }
+
+    #[test]
+    fn test_prune_to_size() {
+        let mut p = Pool::new(usize::MAX);
+        p.observe_transaction(0, t(10), H1);
+        // H2 gets a body; H1 isn't in this snapshot, so it's marked
+        // disappeared rather than removed outright.
+        p.observe_pool(0, t(20), make_pool(vec![H2]));
+        assert_eq!(p.len(), 2);
+
+        let h2_size = p.content_at(t(20)).get(&H2).unwrap().size_bytes();
+        assert_eq!(p.size_bytes(), HASH_ONLY_SIZE_BYTES + h2_size);
+
+        // Under budget: nothing is evicted.
+        p.prune_to_size(p.size_bytes());
+        assert_eq!(p.len(), 2);
+
+        // Only the disappeared H1 needs to go to get under budget, even
+        // though it was seen earlier than H2.
+        p.prune_to_size(h2_size);
+        assert_eq!(p.len(), 1);
+        assert!(p.content_at(t(20)).contains_key(&H2));
+        assert_eq!(p.size_bytes(), h2_size);
+    }
+
+    #[test]
+    fn test_evict() {
+        let mut p = Pool::new(1);
+        p.observe_transaction(0, t(10), H1);
+        p.observe_transaction(0, t(20), H2);
+        assert_eq!(p.content_at(t(20)).len(), 2);
+
+        // H1 is the oldest entry, but it's not yet outside the safe cutoff,
+        // so nothing is evicted even though the pool is over max_size.
+        p.evict(t(10));
+        assert_eq!(p.content_at(t(20)).len(), 2);
+
+        // Once the cutoff passes H1's first_seen, it's evicted to bring the
+        // pool back down to max_size.
+        p.evict(t(11));
+        assert_eq!(p.content_at(t(20)).len(), 1);
+        assert_content(
+            p.content_at(t(20)),
+            vec![(H2, false, vec![t(20)], None)].into_iter().collect(),
+        );
+    }
 }
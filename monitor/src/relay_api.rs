@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+
+use futures::stream::{self, StreamExt};
+use serde::Deserialize;
+use thiserror::Error;
+use url::Url;
+
+use crate::{metrics, types::H256};
+
+#[derive(Error, Debug)]
+pub enum RelayAPIError {
+    #[error("error fetching {requested}")]
+    ReqwestError {
+        source: reqwest::Error,
+        requested: String,
+    },
+}
+
+/// A single delivered-payload record from a relay's
+/// `/relay/v1/data/bidtraces/proposer_payload_delivered` endpoint.
+#[derive(Deserialize, Debug, Clone)]
+struct BidTrace {
+    block_hash: H256,
+    builder_pubkey: String,
+}
+
+/// Which relay (if any) delivered a block's payload, and the builder that
+/// built it, so misses can be segmented by builder/relay instead of being
+/// attributed outright to local proposer censorship.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayProvenance {
+    pub relay_url: Url,
+    pub builder_pubkey: String,
+}
+
+/// Query every relay in `relay_urls` for its delivered-payload feed at
+/// `slot`, concurrently, and return the provenance of whichever one reports
+/// `block_hash`. A relay that errors is logged and treated the same as one
+/// that simply didn't deliver the block, since a relay that lost the
+/// builder auction for a slot looks the same as one that's unreachable.
+async fn fetch_relay_provenance(
+    relay_urls: &[Url],
+    slot: u64,
+    block_hash: H256,
+) -> Option<RelayProvenance> {
+    let results = stream::iter(relay_urls.iter().cloned())
+        .map(|relay_url| async move {
+            let bid_traces = fetch_bid_traces(&relay_url, slot).await;
+            (relay_url, bid_traces)
+        })
+        .buffer_unordered(relay_urls.len().max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    for (relay_url, bid_traces) in results {
+        match bid_traces {
+            Ok(bid_traces) => {
+                if let Some(trace) = bid_traces.into_iter().find(|t| t.block_hash == block_hash) {
+                    return Some(RelayProvenance {
+                        relay_url,
+                        builder_pubkey: trace.builder_pubkey,
+                    });
+                }
+            }
+            Err(e) => log::warn!(
+                "failed to fetch bid traces for slot {} from relay {}: {}",
+                slot,
+                relay_url,
+                e
+            ),
+        }
+    }
+    None
+}
+
+async fn fetch_bid_traces(relay_url: &Url, slot: u64) -> Result<Vec<BidTrace>, RelayAPIError> {
+    let url = relay_url
+        .join("/relay/v1/data/bidtraces/proposer_payload_delivered")
+        .unwrap();
+    reqwest::Client::new()
+        .get(url)
+        .query(&[("slot", slot.to_string())])
+        .send()
+        .await
+        .map_err(|e| RelayAPIError::ReqwestError {
+            source: e,
+            requested: String::from("bid traces"),
+        })?
+        .error_for_status()
+        .map_err(|e| RelayAPIError::ReqwestError {
+            source: e,
+            requested: String::from("bid traces"),
+        })?
+        .json::<Vec<BidTrace>>()
+        .await
+        .map_err(|e| RelayAPIError::ReqwestError {
+            source: e,
+            requested: String::from("bid traces"),
+        })
+}
+
+/// Caches relay provenance lookups by slot, so a backlog of queued blocks
+/// (or re-processing around a reorg) doesn't re-query every configured
+/// relay for a slot it's already resolved. Mirrors `NonceCache`'s
+/// bounded-size eviction, but keyed on slot alone: a lookup for a different
+/// block at an already-cached slot is served the result cached for the
+/// first block looked up there.
+pub struct RelayCache {
+    entries: BTreeMap<u64, Option<RelayProvenance>>,
+    max_size: usize,
+}
+
+impl RelayCache {
+    pub fn new(max_size: usize) -> Self {
+        RelayCache {
+            entries: BTreeMap::new(),
+            max_size,
+        }
+    }
+
+    /// Get the relay provenance for `block_hash` at `slot`, fetching it from
+    /// `relay_urls` on a cache miss.
+    pub async fn get(
+        &mut self,
+        relay_urls: &[Url],
+        slot: u64,
+        block_hash: H256,
+    ) -> Option<RelayProvenance> {
+        if let Some(cached) = self.entries.get(&slot) {
+            metrics::RELAY_CACHE_HITS.inc();
+            return cached.clone();
+        }
+        metrics::RELAY_CACHE_MISSES.inc();
+
+        let provenance = fetch_relay_provenance(relay_urls, slot, block_hash).await;
+        if provenance.is_some() {
+            metrics::RELAY_PROVENANCE_FOUND.inc();
+        }
+        self.entries.insert(slot, provenance.clone());
+        self.prune();
+        provenance
+    }
+
+    fn prune(&mut self) {
+        while self.entries.len() > self.max_size {
+            let Some((&slot, _)) = self.entries.iter().next() else {
+                break;
+            };
+            self.entries.remove(&slot);
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::head_history::{HeadHistory, ObservedHead};
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("error reading snapshot: {0}")]
+    Read(std::io::Error),
+    #[error("error writing snapshot: {0}")]
+    Write(std::io::Error),
+    #[error("error (de)serializing snapshot: {0}")]
+    Serde(#[from] serde_json::Error),
+}
+
+/// Write the head history to `path`. Callers should `prune` the history
+/// beforehand so the snapshot only contains observations still relevant at or
+/// after the current cutoff.
+pub fn save_head_history(path: &Path, head_history: &HeadHistory) -> Result<(), SnapshotError> {
+    let observed_heads: Vec<&ObservedHead> = head_history.observed_heads().collect();
+    let serialized = serde_json::to_vec(&observed_heads)?;
+    std::fs::write(path, serialized).map_err(SnapshotError::Write)
+}
+
+/// Load a head history previously written with `save_head_history`. Observed
+/// heads are re-inserted via `HeadHistory::from_observed_heads` rather than
+/// trusted verbatim, so the ordering invariant is re-established regardless
+/// of what is on disk.
+pub fn load_head_history(path: &Path) -> Result<HeadHistory, SnapshotError> {
+    let content = std::fs::read(path).map_err(SnapshotError::Read)?;
+    let observed_heads: Vec<ObservedHead> = serde_json::from_slice(&content)?;
+    Ok(HeadHistory::from_observed_heads(observed_heads))
+}
@@ -1,11 +1,20 @@
+use std::path::PathBuf;
+
 use ethers::types::Transaction;
+use tokio::sync::mpsc;
 
 use crate::{
-    analyze::{analyze, Analysis},
+    analyze::{analyze, Analysis, AttestationInfo},
+    consensus_api::ConsensusProvider,
     head_history::HeadHistory,
+    metrics,
     nonce_cache::NonceCache,
-    pool::Pool,
-    types::{BeaconBlock, NodeKey, Timestamp, TxHash, TxpoolContent},
+    pool::{Pool, PoolEvent},
+    relay_api::RelayProvenance,
+    snapshot,
+    types::{
+        BeaconBlock, NodeKey, Timestamp, TxHash, TxpoolContent, ATTESTATION_DEADLINE_SECONDS,
+    },
     watch::{Event, NodeConfig},
 };
 
@@ -15,31 +24,86 @@ pub struct State {
     pool: Pool,
     head_history: HeadHistory,
     nonce_cache: NonceCache,
+    consensus_provider: ConsensusProvider,
 
-    analysis_queue: Vec<BeaconBlock<Transaction>>,
+    analysis_queue: Vec<(BeaconBlock<Transaction>, Timestamp, Option<RelayProvenance>)>,
 
     quorum: usize,
+    reorg_threshold_weight_gwei: u64,
+    pool_max_bytes: usize,
+
+    head_history_snapshot_path: Option<PathBuf>,
+    head_history_snapshot_interval: Timestamp,
+    last_head_history_snapshot: Timestamp,
 }
 
 impl State {
-    pub fn new(node_config: &NodeConfig) -> Self {
-        let pool = Pool::new();
-        let head_history = HeadHistory::new();
+    pub fn new(node_config: &NodeConfig, pool_event_tx: mpsc::Sender<PoolEvent>) -> Self {
+        let quorum = node_config.execution_ws_urls.len();
+        let pool = Pool::new(node_config.pool_max_size).with_events(quorum, pool_event_tx);
+        let head_history = match &node_config.head_history_snapshot_path {
+            Some(path) => match snapshot::load_head_history(path) {
+                Ok(head_history) => {
+                    log::info!("restored head history from snapshot at {}", path.display());
+                    head_history
+                }
+                Err(e) => {
+                    log::warn!(
+                        "could not restore head history from snapshot at {}, starting \
+                         empty: {}",
+                        path.display(),
+                        e
+                    );
+                    HeadHistory::new()
+                }
+            },
+            None => HeadHistory::new(),
+        };
 
         let nonce_cache_provider = node_config.execution_http_provider();
-        let nonce_cache = NonceCache::new(nonce_cache_provider);
+        let nonce_cache = NonceCache::new(nonce_cache_provider, node_config.nonce_cache_size);
+
+        let consensus_provider = node_config.consensus_provider();
 
         State {
             pool,
             head_history,
             nonce_cache,
+            consensus_provider,
 
             analysis_queue: Vec::new(),
 
-            quorum: node_config.execution_ws_urls.len(),
+            quorum,
+            reorg_threshold_weight_gwei: node_config.reorg_threshold_weight_gwei,
+            pool_max_bytes: node_config.pool_max_bytes,
+
+            head_history_snapshot_path: node_config.head_history_snapshot_path.clone(),
+            head_history_snapshot_interval: node_config.head_history_snapshot_interval_seconds,
+            last_head_history_snapshot: 0,
         }
     }
 
+    /// Write a snapshot of the (already pruned) head history to disk if a
+    /// snapshot path is configured and the snapshot interval has elapsed.
+    fn maybe_snapshot_head_history(&mut self, t: Timestamp) {
+        let Some(path) = &self.head_history_snapshot_path else {
+            return;
+        };
+        if t.saturating_sub(self.last_head_history_snapshot) < self.head_history_snapshot_interval
+        {
+            return;
+        }
+        match snapshot::save_head_history(path, &self.head_history) {
+            Ok(()) => log::debug!("wrote head history snapshot to {}", path.display()),
+            Err(e) => log::warn!(
+                "failed to write head history snapshot to {}: {}",
+                path.display(),
+                e
+            ),
+        }
+        self.last_head_history_snapshot = t;
+    }
+
     pub async fn process_event(&mut self, event: Event) -> Vec<Analysis> {
         match event {
             Event::NewTransaction {
@@ -53,7 +117,11 @@ impl State {
             Event::NewHead {
                 beacon_block,
                 timestamp,
-            } => self.process_new_head_event(beacon_block, timestamp).await,
+                relay_provenance,
+            } => {
+                self.process_new_head_event(beacon_block, timestamp, relay_provenance)
+                    .await
+            }
             Event::TxpoolContent {
                 node,
                 content,
@@ -72,6 +140,8 @@ impl State {
         t: Timestamp,
     ) -> Vec<Analysis> {
         self.pool.observe_transaction(node, t, hash);
+        self.pool.evict(t.saturating_sub(PRUNE_DELAY));
+        self.pool.prune_to_size(self.pool_max_bytes);
         Vec::new()
     }
 
@@ -83,13 +153,17 @@ impl State {
     ) -> Vec<Analysis> {
         self.pool.observe_pool(node, t, content);
         self.pool.prune(t.saturating_sub(PRUNE_DELAY));
+        self.pool.evict(t.saturating_sub(PRUNE_DELAY));
+        self.pool.prune_to_size(self.pool_max_bytes);
 
         let beacon_blocks = self.analysis_queue.clone();
         self.analysis_queue.clear();
 
         let mut analyses = Vec::new();
-        for beacon_block in beacon_blocks {
-            let analysis = self.analyse_beacon_block(&beacon_block).await;
+        for (beacon_block, observed_at, relay_provenance) in beacon_blocks {
+            let analysis = self
+                .analyse_beacon_block(&beacon_block, observed_at, relay_provenance)
+                .await;
             if let Some(analysis) = analysis {
                 analyses.push(analysis);
             }
@@ -101,16 +175,40 @@ impl State {
         &mut self,
         beacon_block: BeaconBlock<Transaction>,
         t: Timestamp,
+        relay_provenance: Option<RelayProvenance>,
     ) -> Vec<Analysis> {
-        self.head_history.observe(t, beacon_block.clone());
+        if let Some(reorg) = self.head_history.observe(t, beacon_block.clone()) {
+            metrics::REORGS.inc();
+            metrics::REORG_DEPTH.set(reorg.depth as f64);
+            metrics::ORPHANED_BLOCKS.inc_by(reorg.orphaned_roots.len() as u64);
+
+            // A transaction "included" in an orphaned block was not actually
+            // censored if the new canonical chain still needs to include it,
+            // so re-queue the orphaned blocks for analysis. The orphaned
+            // block's original relay provenance isn't retained by head
+            // history, so it's re-queued without one rather than re-querying
+            // relays for a block that's no longer canonical.
+            for root in &reorg.orphaned_roots {
+                if let Some(orphaned_block) = self.head_history.get_by_root(*root) {
+                    log::info!(
+                        "re-queueing orphaned block {} for analysis after reorg",
+                        orphaned_block
+                    );
+                    self.analysis_queue.push((orphaned_block, t, None));
+                }
+            }
+        }
         self.head_history.prune(t.saturating_sub(PRUNE_DELAY));
-        self.analysis_queue.push(beacon_block);
+        self.maybe_snapshot_head_history(t);
+        self.analysis_queue.push((beacon_block, t, relay_provenance));
         Vec::new()
     }
 
     async fn analyse_beacon_block(
         &mut self,
         beacon_block: &BeaconBlock<Transaction>,
+        observed_at: Timestamp,
+        relay_provenance: Option<RelayProvenance>,
     ) -> Option<Analysis> {
         self.nonce_cache.apply_block(beacon_block.clone());
 
@@ -139,7 +237,17 @@ impl State {
             }
         }
 
-        let analysis = analyze(beacon_block, &self.pool, &mut self.nonce_cache, self.quorum).await;
+        let attestation = self.fetch_attestation_info(beacon_block, observed_at).await;
+
+        let analysis = analyze(
+            beacon_block,
+            &self.pool,
+            &mut self.nonce_cache,
+            self.quorum,
+            attestation,
+            relay_provenance,
+        )
+        .await;
         match analysis {
             Ok(a) => Some(a),
             Err(e) => {
@@ -148,4 +256,36 @@ impl State {
             }
         }
     }
+
+    /// Fetch the attesting weight a block received and determine whether it
+    /// arrived after its slot's attestation deadline. Returns `None` if the
+    /// weight could not be fetched, e.g. because the block fell out of the
+    /// consensus node's fork choice store.
+    async fn fetch_attestation_info(
+        &self,
+        beacon_block: &BeaconBlock<Transaction>,
+        observed_at: Timestamp,
+    ) -> Option<AttestationInfo> {
+        let is_late = observed_at.saturating_sub(beacon_block.proposal_time())
+            > ATTESTATION_DEADLINE_SECONDS;
+        match self
+            .consensus_provider
+            .fetch_fork_choice_weight(beacon_block.root)
+            .await
+        {
+            Ok(weight_gwei) => Some(AttestationInfo {
+                weight_gwei,
+                threshold_gwei: self.reorg_threshold_weight_gwei,
+                is_late,
+            }),
+            Err(e) => {
+                log::warn!(
+                    "failed to fetch attestation weight for block {}: {}",
+                    beacon_block,
+                    e
+                );
+                None
+            }
+        }
+    }
 }
@@ -5,6 +5,13 @@ pub use ethers::types::{
 };
 pub type Timestamp = u64;
 
+/// Seconds per slot on mainnet.
+pub const SECONDS_PER_SLOT: u64 = 12;
+/// Seconds into a slot after which honest validators are expected to have
+/// attested, per `INTERVALS_PER_SLOT`. A head arriving after this deadline
+/// may be re-orged by proposer boost.
+pub const ATTESTATION_DEADLINE_SECONDS: u64 = SECONDS_PER_SLOT / 3;
+
 /// ChronologyError is returned if events are reported in wrong order.
 #[derive(Debug, PartialEq)]
 pub struct ChronologyError;
@@ -1,10 +1,12 @@
 use std::collections::{vec_deque, VecDeque};
 
+use serde::{Deserialize, Serialize};
+
 use crate::types::Timestamp;
 
 /// Observation represents a check if an item is visible or not at a certain
 /// point in time.
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum Observation {
     Seen(Timestamp),
     NotSeen(Timestamp),
@@ -187,6 +189,27 @@ impl IntoIterator for Observations {
     }
 }
 
+impl Serialize for Observations {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.iter().collect::<Vec<_>>().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Observations {
+    /// Rebuild from a serialized sequence of observations via `insert`, so the
+    /// squashing invariants are re-established rather than trusted from disk.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let observations = Vec::<Observation>::deserialize(deserializer)?;
+        Ok(Observations::from_iter(observations))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -15,7 +15,10 @@ use tokio::{
 use crate::{
     cli::Config,
     consensus_api::{ConsensusAPIError, ConsensusProvider},
+    execution_quorum::QuorumExecutionProvider,
     metrics,
+    node_client::{self, NodeClient},
+    relay_api::{RelayCache, RelayProvenance},
     types::{url_with_path, BeaconBlock, NewBeaconHeadEvent, NodeKey, TxHash, TxpoolContent},
 };
 
@@ -25,6 +28,16 @@ pub struct NodeConfig {
     pub execution_http_url: url::Url,
     pub execution_ws_urls: Vec<url::Url>,
     pub consensus_http_url: url::Url,
+    pub reorg_threshold_weight_gwei: u64,
+    pub nonce_cache_size: usize,
+    pub pool_max_size: usize,
+    pub pool_max_bytes: usize,
+    pub head_history_snapshot_path: Option<std::path::PathBuf>,
+    pub head_history_snapshot_interval_seconds: u64,
+    pub relay_urls: Vec<url::Url>,
+    pub relay_cache_size: usize,
+    pub execution_http_quorum_urls: Vec<url::Url>,
+    pub execution_quorum_threshold: usize,
 }
 
 impl NodeConfig {
@@ -33,6 +46,19 @@ impl NodeConfig {
             execution_http_url: config.execution_http_url.clone(),
             execution_ws_urls: config.execution_ws_urls(),
             consensus_http_url: config.consensus_http_url.clone(),
+            reorg_threshold_weight_gwei: config.reorg_threshold_weight_gwei,
+            nonce_cache_size: config.nonce_cache_size,
+            pool_max_size: config.pool_max_size,
+            pool_max_bytes: config.pool_max_bytes,
+            head_history_snapshot_path: config
+                .head_history_snapshot_path
+                .as_ref()
+                .map(std::path::PathBuf::from),
+            head_history_snapshot_interval_seconds: config.head_history_snapshot_interval_seconds,
+            relay_urls: config.relay_urls.clone(),
+            relay_cache_size: config.relay_cache_size,
+            execution_http_quorum_urls: config.execution_http_quorum_urls.clone(),
+            execution_quorum_threshold: config.execution_quorum_threshold,
         }
     }
 
@@ -44,6 +70,17 @@ impl NodeConfig {
         Provider::try_from(url).unwrap()
     }
 
+    /// Create a provider for the primary node plus one for each configured
+    /// `execution_http_quorum_urls` entry, for quorum-checked observations.
+    pub fn execution_http_quorum_providers(&self) -> Vec<Provider<Http>> {
+        let mut providers = vec![self.execution_http_provider()];
+        for url in &self.execution_http_quorum_urls {
+            // Unwrapping is fine for the same reason as execution_http_provider.
+            providers.push(Provider::try_from(url.as_str()).unwrap());
+        }
+        providers
+    }
+
     /// Create and connect a websocket provider for each of the nodes at
     /// execution_ws_urls.
     pub async fn execution_ws_providers(&self) -> Result<Vec<Provider<Ws>>, ProviderError> {
@@ -100,6 +137,7 @@ pub enum Event {
     NewHead {
         beacon_block: BeaconBlock<Transaction>,
         timestamp: DateTime<Utc>,
+        relay_provenance: Option<RelayProvenance>,
     },
     TxpoolContent {
         node: NodeKey,
@@ -202,6 +240,21 @@ async fn watch_heads(node_config: NodeConfig, tx: Sender<Event>) -> Result<(), W
     let exec_provider = node_config.execution_http_provider();
     let cons_provider = node_config.consensus_provider();
 
+    // Detected once at connection time: the txpool_content response shape
+    // (and whether it's supported at all) differs by execution client.
+    let node_client = node_client::detect(&exec_provider)
+        .await
+        .unwrap_or(NodeClient::Unknown);
+    log::info!("execution node 0 detected as {:?}", node_client);
+
+    let mut relay_cache = RelayCache::new(node_config.relay_cache_size);
+
+    let quorum_provider = QuorumExecutionProvider::new(
+        node_config.execution_http_quorum_providers(),
+        node_config.execution_quorum_threshold,
+    )
+    .await;
+
     let mut url = url_with_path(&node_config.consensus_http_url, "/eth/v1/events");
     url.set_query(Some("topics=head"));
     let request = reqwest::Client::new().get(url);
@@ -234,8 +287,30 @@ async fn watch_heads(node_config: NodeConfig, tx: Sender<Event>) -> Result<(), W
                     es.close();
                     return Err(WatchError::from(e));
                 }
-                let beacon_block =
-                    BeaconBlock::new(beacon_block_without_root.unwrap(), event.block);
+                let (beacon_block_without_root, withdrawals) = beacon_block_without_root.unwrap();
+                if !withdrawals.is_empty() {
+                    log::debug!(
+                        "block at root {} has {} withdrawal(s)",
+                        event.block,
+                        withdrawals.len(),
+                    );
+                }
+                let beacon_block = BeaconBlock::new(beacon_block_without_root, event.block);
+
+                let relay_provenance = relay_cache
+                    .get(
+                        &node_config.relay_urls,
+                        beacon_block.slot.as_u64(),
+                        beacon_block.body.execution_payload.block_hash,
+                    )
+                    .await;
+
+                // Only used for its disagreement metric/logging here: the
+                // canonical block itself is already known from the consensus
+                // side above.
+                quorum_provider
+                    .get_block_by_hash(beacon_block.body.execution_payload.block_hash)
+                    .await;
 
                 let relative_capacity = tx.capacity() as f32 / tx.max_capacity() as f32;
                 if relative_capacity < 0.1 {
@@ -246,6 +321,7 @@ async fn watch_heads(node_config: NodeConfig, tx: Sender<Event>) -> Result<(), W
                     .send(Event::NewHead {
                         beacon_block,
                         timestamp: t,
+                        relay_provenance,
                     })
                     .await
                 {
@@ -260,10 +336,18 @@ async fn watch_heads(node_config: NodeConfig, tx: Sender<Event>) -> Result<(), W
         }
 
         let fetch_pool_t0 = Instant::now();
-        let content = exec_provider.txpool_content().await?;
+        let content = quorum_provider.fetch_txpool_content().await;
         metrics::FETCH_POOL_DURATION
             .observe(Instant::elapsed(&fetch_pool_t0).as_millis() as f64 / 1000.);
 
+        let content = match content {
+            Some(content) => content,
+            None => {
+                metrics::TXPOOL_UNSUPPORTED.with_label_values(&["0"]).inc();
+                continue;
+            }
+        };
+
         let event = Event::TxpoolContent {
             node: 0,
             content,
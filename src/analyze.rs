@@ -1,6 +1,8 @@
 use std::{
     cmp::min,
     collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
     time::{Duration, Instant},
 };
 
@@ -8,19 +10,79 @@ use chrono::{DateTime, Utc};
 use thiserror::Error;
 
 use crate::{
+    code_cache::{CodeCache, CodeCacheError},
     metrics,
     nonce_cache::{NonceCache, NonceCacheError},
     pool::{ObservedTransaction, Pool},
     types::{Address, BeaconBlock, ExecutionPayload, Transaction, TxHash, U256},
 };
 
+/// Gas charged per blob, as defined by EIP-4844.
+const GAS_PER_BLOB: u64 = 131072;
+/// Maximum blob gas a single block may consume, as defined by EIP-4844.
+const MAX_BLOB_GAS_PER_BLOCK: u64 = 786432;
+/// Minimum possible blob base fee, as defined by EIP-4844.
+const MIN_BLOB_BASE_FEE: u64 = 1;
+/// Divisor controlling how quickly the blob base fee adjusts, as defined by
+/// EIP-4844.
+const BLOB_BASE_FEE_UPDATE_FRACTION: u64 = 3338477;
+
 /// Possible justified reasons why a transaction is not in a block.
-#[derive(Debug, Hash, PartialEq, Eq)]
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
 pub enum NonInclusionReason {
     NotEnoughSpace,
     BaseFeeTooLow,
     TipTooLow,
-    NonceMismatch,
+    NonceTooLow,
+    NonceGap,
+    NotEnoughBlobSpace,
+    BlobBaseFeeTooLow,
+    SenderHasCode,
+}
+
+impl NonInclusionReason {
+    /// The stable, machine-readable name used for persistence and for
+    /// filtering misses by reason over the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NonInclusionReason::NotEnoughSpace => "not_enough_space",
+            NonInclusionReason::BaseFeeTooLow => "base_fee_too_low",
+            NonInclusionReason::TipTooLow => "tip_too_low",
+            NonInclusionReason::NonceTooLow => "nonce_too_low",
+            NonInclusionReason::NonceGap => "nonce_gap",
+            NonInclusionReason::NotEnoughBlobSpace => "not_enough_blob_space",
+            NonInclusionReason::BlobBaseFeeTooLow => "blob_base_fee_too_low",
+            NonInclusionReason::SenderHasCode => "sender_has_code",
+        }
+    }
+}
+
+impl fmt::Display for NonInclusionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("unknown non-inclusion reason: {0}")]
+pub struct ParseNonInclusionReasonError(String);
+
+impl FromStr for NonInclusionReason {
+    type Err = ParseNonInclusionReasonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "not_enough_space" => Ok(NonInclusionReason::NotEnoughSpace),
+            "base_fee_too_low" => Ok(NonInclusionReason::BaseFeeTooLow),
+            "tip_too_low" => Ok(NonInclusionReason::TipTooLow),
+            "nonce_too_low" => Ok(NonInclusionReason::NonceTooLow),
+            "nonce_gap" => Ok(NonInclusionReason::NonceGap),
+            "not_enough_blob_space" => Ok(NonInclusionReason::NotEnoughBlobSpace),
+            "blob_base_fee_too_low" => Ok(NonInclusionReason::BlobBaseFeeTooLow),
+            "sender_has_code" => Ok(NonInclusionReason::SenderHasCode),
+            _ => Err(ParseNonInclusionReasonError(s.to_string())),
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -29,6 +91,8 @@ enum InclusionCheckError {
     TransactionError(#[from] TransactionError),
     #[error("cannot check inclusion due to nonce cache error")]
     NonceCacheError(#[from] NonceCacheError),
+    #[error("cannot check inclusion due to code cache error")]
+    CodeCacheError(#[from] CodeCacheError),
 }
 
 #[derive(Debug, Error)]
@@ -46,20 +110,32 @@ pub enum TransactionError {
 }
 
 /// Perform all inclusion checks.
+/// Check sender-eligibility, fee- and nonce-eligibility for inclusion.
+/// Deliberately excludes `check_not_enough_space`: whether a transaction
+/// would have fit isn't a per-transaction question once more than one
+/// fee-eligible transaction is competing for the same space, so that's
+/// decided afterwards by a block-wide greedy fit check over all
+/// fee-eligible candidates (see `apply_gas_fit_check`).
 async fn check_inclusion(
     transaction: &Transaction,
     beacon_block: &BeaconBlock<Transaction>,
     nonce_cache: &mut NonceCache,
+    code_cache: &mut CodeCache,
+    tip_percentile: u64,
 ) -> Result<Option<NonInclusionReason>, InclusionCheckError> {
     let exec = &beacon_block.body.execution_payload;
-    if check_not_enough_space(transaction, exec) {
-        Ok(Some(NonInclusionReason::NotEnoughSpace))
+    if check_sender_has_code(transaction, beacon_block, code_cache).await? {
+        Ok(Some(NonInclusionReason::SenderHasCode))
+    } else if check_not_enough_blob_space(transaction, exec)? {
+        Ok(Some(NonInclusionReason::NotEnoughBlobSpace))
     } else if check_base_fee_too_low(transaction, exec)? {
         Ok(Some(NonInclusionReason::BaseFeeTooLow))
-    } else if check_tip_too_low(transaction, exec)? {
+    } else if check_blob_base_fee_too_low(transaction, exec)? {
+        Ok(Some(NonInclusionReason::BlobBaseFeeTooLow))
+    } else if check_tip_too_low(transaction, exec, tip_percentile)? {
         Ok(Some(NonInclusionReason::TipTooLow))
-    } else if check_nonce_mismatch(transaction, beacon_block, nonce_cache).await? {
-        Ok(Some(NonInclusionReason::NonceMismatch))
+    } else if let Some(reason) = check_nonce_mismatch(transaction, beacon_block, nonce_cache).await? {
+        Ok(Some(reason))
     } else {
         Ok(None)
     }
@@ -94,7 +170,7 @@ pub fn get_tip(transaction: &Transaction, base_fee: U256) -> Result<U256, Transa
         } else {
             Ok(gas_price - base_fee)
         }
-    } else if t == 2 {
+    } else if t == 2 || t == 3 {
         let max_fee_per_gas =
             transaction
                 .max_fee_per_gas
@@ -123,8 +199,70 @@ pub fn get_tip(transaction: &Transaction, base_fee: U256) -> Result<U256, Transa
     }
 }
 
-/// Check if there is not enough space left in the block to include the
-/// transaction.
+/// Get the effective price per unit of gas a transaction would pay in a
+/// block with given base fee, i.e. what a miner/validator actually receives
+/// plus the base fee burned.
+fn get_effective_gas_price(
+    transaction: &Transaction,
+    base_fee: U256,
+) -> Result<U256, TransactionError> {
+    let t = get_transaction_type(transaction)?;
+    if t == 0 || t == 1 {
+        transaction
+            .gas_price
+            .ok_or(TransactionError::MissingRequiredField {
+                name: String::from("gasPrice"),
+            })
+    } else if t == 2 || t == 3 {
+        let max_fee_per_gas =
+            transaction
+                .max_fee_per_gas
+                .ok_or(TransactionError::MissingRequiredField {
+                    name: String::from("maxFeePerGas"),
+                })?;
+        let max_priority_fee_per_gas =
+            transaction
+                .max_priority_fee_per_gas
+                .ok_or(TransactionError::MissingRequiredField {
+                    name: String::from("maxPriorityFeePerGas"),
+                })?;
+        Ok(min(max_fee_per_gas, base_fee + max_priority_fee_per_gas))
+    } else {
+        Err(TransactionError::UnsupportedType {
+            transaction_type: t,
+        })
+    }
+}
+
+/// Check if `new_value` bumps `old_value` by at least the minimum 12.5%
+/// required by execution clients to accept a replacement transaction.
+fn is_fee_bumped(old_value: U256, new_value: U256) -> bool {
+    new_value >= old_value + old_value / 8
+}
+
+/// Check whether `in_block_tx` is a legitimate fee-bumped replacement of
+/// `pool_tx`, which shares its sender and nonce. Both the tip and the
+/// effective gas price paid at `base_fee` must be bumped by at least the
+/// minimum 12.5% bump rule; otherwise the in-block transaction is a
+/// suspiciously cheap displacement rather than a genuine replacement.
+fn is_legitimate_replacement(
+    pool_tx: &Transaction,
+    in_block_tx: &Transaction,
+    base_fee: U256,
+) -> Result<bool, TransactionError> {
+    let old_tip = get_tip(pool_tx, base_fee)?;
+    let new_tip = get_tip(in_block_tx, base_fee)?;
+    let old_price = get_effective_gas_price(pool_tx, base_fee)?;
+    let new_price = get_effective_gas_price(in_block_tx, base_fee)?;
+    Ok(is_fee_bumped(old_tip, new_tip) && is_fee_bumped(old_price, new_price))
+}
+
+/// Check if the transaction alone would not have fit in the block's unused
+/// gas, ignoring any other transaction that might also have been competing
+/// for that space. Used by the single-transaction `check` CLI command;
+/// `analyze` instead runs `apply_gas_fit_check` over all fee-eligible
+/// candidates at once, since this check alone would let multiple candidates
+/// each "fit" in gas that only one of them could actually use.
 pub fn check_not_enough_space(
     transaction: &Transaction,
     exec: &ExecutionPayload<Transaction>,
@@ -145,7 +283,7 @@ pub fn check_base_fee_too_low(
             .ok_or(TransactionError::MissingRequiredField {
                 name: String::from("gasPrice"),
             })?
-    } else if t == 2 {
+    } else if t == 2 || t == 3 {
         transaction
             .max_fee_per_gas
             .ok_or(TransactionError::MissingRequiredField {
@@ -159,14 +297,97 @@ pub fn check_base_fee_too_low(
     Ok(max_base_fee < exec.base_fee_per_gas)
 }
 
-/// Check if the transaction doesn't pay a high enough tip.
+/// Check if there is not enough room left in the block's blob gas budget for
+/// the transaction's blobs. Non-blob transactions always pass this check.
+pub fn check_not_enough_blob_space(
+    transaction: &Transaction,
+    exec: &ExecutionPayload<Transaction>,
+) -> Result<bool, TransactionError> {
+    let t = get_transaction_type(transaction)?;
+    if t != 3 {
+        return Ok(false);
+    }
+    let blob_gas_used = exec
+        .blob_gas_used
+        .ok_or(TransactionError::MissingRequiredField {
+            name: String::from("blobGasUsed"),
+        })?
+        .as_u64();
+    let blob_versioned_hashes =
+        transaction
+            .blob_versioned_hashes
+            .as_ref()
+            .ok_or(TransactionError::MissingRequiredField {
+                name: String::from("blobVersionedHashes"),
+            })?;
+    let tx_blob_gas = blob_versioned_hashes.len() as u64 * GAS_PER_BLOB;
+    Ok(blob_gas_used + tx_blob_gas > MAX_BLOB_GAS_PER_BLOCK)
+}
+
+/// Check if the transaction doesn't pay a high enough blob base fee.
+/// Non-blob transactions always pass this check.
+pub fn check_blob_base_fee_too_low(
+    transaction: &Transaction,
+    exec: &ExecutionPayload<Transaction>,
+) -> Result<bool, TransactionError> {
+    let t = get_transaction_type(transaction)?;
+    if t != 3 {
+        return Ok(false);
+    }
+    let excess_blob_gas = exec
+        .excess_blob_gas
+        .ok_or(TransactionError::MissingRequiredField {
+            name: String::from("excessBlobGas"),
+        })?
+        .as_u64();
+    let max_fee_per_blob_gas =
+        transaction
+            .max_fee_per_blob_gas
+            .ok_or(TransactionError::MissingRequiredField {
+                name: String::from("maxFeePerBlobGas"),
+            })?;
+    let blob_base_fee = get_blob_base_fee(excess_blob_gas);
+    Ok(max_fee_per_blob_gas < blob_base_fee)
+}
+
+/// Derive the blob base fee for a block from its excess blob gas, as defined
+/// by EIP-4844.
+fn get_blob_base_fee(excess_blob_gas: u64) -> U256 {
+    fake_exponential(
+        MIN_BLOB_BASE_FEE,
+        excess_blob_gas,
+        BLOB_BASE_FEE_UPDATE_FRACTION,
+    )
+}
+
+/// Approximate `factor * e ** (numerator / denominator)` using a Taylor
+/// series, as defined by EIP-4844.
+fn fake_exponential(factor: u64, numerator: u64, denominator: u64) -> U256 {
+    let factor = U256::from(factor);
+    let numerator = U256::from(numerator);
+    let denominator = U256::from(denominator);
+
+    let mut i = U256::from(1);
+    let mut output = U256::zero();
+    let mut numerator_accum = factor * denominator;
+    while !numerator_accum.is_zero() {
+        output += numerator_accum;
+        numerator_accum = numerator_accum * numerator / (denominator * i);
+        i += U256::from(1);
+    }
+    output / denominator
+}
+
+/// Check if the transaction doesn't pay a high enough tip, i.e. less than
+/// the block's `tip_percentile`-th percentile tip (see `get_percentile_tip`).
 pub fn check_tip_too_low(
     transaction: &Transaction,
     exec: &ExecutionPayload<Transaction>,
+    tip_percentile: u64,
 ) -> Result<bool, TransactionError> {
-    let median_tip = get_median_tip(&exec.transactions, exec.base_fee_per_gas);
+    let threshold_tip = get_percentile_tip(&exec.transactions, exec.base_fee_per_gas, tip_percentile);
     match get_tip(transaction, exec.base_fee_per_gas) {
-        Ok(tip) => Ok(tip < median_tip),
+        Ok(tip) => Ok(tip < threshold_tip),
         Err(TransactionError::FeeTooLow {
             max_fee: _,
             base_fee: _,
@@ -176,14 +397,40 @@ pub fn check_tip_too_low(
     }
 }
 
-/// Check if there is a mismatch between transaction and account nonce.
+/// Check whether the transaction's sender had non-empty code as of the
+/// block's pre-state. EIP-3607 forbids such a transaction from being
+/// included regardless of how it's priced or nonced, so this is excluded
+/// from `missing_transactions` rather than counted as censorship.
+pub async fn check_sender_has_code(
+    transaction: &Transaction,
+    beacon_block: &BeaconBlock<Transaction>,
+    code_cache: &mut CodeCache,
+) -> Result<bool, CodeCacheError> {
+    code_cache.has_code(&transaction.from, beacon_block).await
+}
+
+/// Check if there is a mismatch between the transaction's nonce and the
+/// account's nonce at the point it would have been included. The account
+/// nonce is advanced by any same-sender transactions already included
+/// earlier in the block, so a transaction that would have been the n-th of
+/// its sender in the block is compared against the nonce after the first
+/// n-1 are applied.
 pub async fn check_nonce_mismatch(
     transaction: &Transaction,
     beacon_block: &BeaconBlock<Transaction>,
     nonce_cache: &mut NonceCache,
-) -> Result<bool, NonceCacheError> {
-    let nonce = nonce_cache.get(&transaction.from, beacon_block).await?;
-    Ok(nonce != transaction.nonce.as_u64())
+) -> Result<Option<NonInclusionReason>, NonceCacheError> {
+    // The cache answers with the nonce after the block's own transactions,
+    // so it is already advanced past every same-sender one in it.
+    let effective_nonce = nonce_cache.get(&transaction.from, beacon_block).await?;
+    let tx_nonce = transaction.nonce.as_u64();
+    if tx_nonce < effective_nonce {
+        Ok(Some(NonInclusionReason::NonceTooLow))
+    } else if tx_nonce > effective_nonce {
+        Ok(Some(NonInclusionReason::NonceGap))
+    } else {
+        Ok(None)
+    }
 }
 
 /// Get the minimum tip of the given transactions. Transactions with missing
@@ -198,27 +445,49 @@ pub fn get_min_nonzero_tip(transactions: &[Transaction], base_fee: U256) -> U256
         .unwrap_or(U256::MAX)
 }
 
-/// Get the median tip amount of the given transactions. Transactions with
-/// missing required fields are ignored. If there's no transactions to consider,
-/// returns the maximum of U256.
-pub fn get_median_tip(transactions: &[Transaction], base_fee: U256) -> U256 {
+/// Get the `percentile`-th percentile tip amount of the given transactions
+/// (0-100), mirroring OpenEthereum's `--gas-price-percentile`. Transactions
+/// with missing required fields are ignored. If there's no transactions to
+/// consider, returns the maximum of U256. The rank `(n - 1) * percentile /
+/// 100` is interpolated between its two neighboring tips when it falls
+/// between them rather than landing exactly on an index.
+pub fn get_percentile_tip(transactions: &[Transaction], base_fee: U256, percentile: u64) -> U256 {
     let mut tips: Vec<U256> = transactions
         .iter()
         .filter_map(|tx| get_tip(tx, base_fee).ok())
         .collect();
-    let n = tips.len();
-    if n == 0 {
-        U256::MAX
+    if tips.is_empty() {
+        return U256::MAX;
+    }
+    tips.sort();
+
+    let n = tips.len() as u64;
+    let scaled_rank = (n - 1) * percentile;
+    let idx = (scaled_rank / 100) as usize;
+    let remainder = scaled_rank % 100;
+    if remainder == 0 || idx + 1 == tips.len() {
+        tips[idx]
     } else {
-        tips.sort();
-        if n % 2 == 0 {
-            (tips[n / 2 - 1] + tips[n / 2]) / 2
-        } else {
-            tips[(n - 1) / 2]
-        }
+        let lo = tips[idx];
+        let hi = tips[idx + 1];
+        lo + (hi - lo) * U256::from(remainder) / U256::from(100)
     }
 }
 
+/// Get the median tip amount of the given transactions, i.e. the 50th
+/// percentile. See `get_percentile_tip`.
+pub fn get_median_tip(transactions: &[Transaction], base_fee: U256) -> U256 {
+    get_percentile_tip(transactions, base_fee, 50)
+}
+
+#[derive(Debug, Error)]
+pub enum AnalyzeError {
+    #[error("nonce cache error")]
+    NonceCacheError(#[from] NonceCacheError),
+    #[error("code cache error")]
+    CodeCacheError(#[from] CodeCacheError),
+}
+
 #[derive(Debug)]
 pub struct Analysis {
     pub beacon_block: BeaconBlock<Transaction>,
@@ -231,6 +500,7 @@ pub struct Analysis {
     pub num_still_propagating: usize,
     pub num_only_tx_hash: usize,
     pub num_replaced_txs: usize,
+    pub num_suspicious_replacements: usize,
     pub non_inclusion_reasons: HashMap<NonInclusionReason, usize>,
     pub duration: Duration,
 }
@@ -244,15 +514,50 @@ pub struct MissedTransaction {
     pub tip: i64,
 }
 
+/// A transaction that passed every fee and nonce eligibility check and is
+/// awaiting the block-wide gas fit check in `apply_gas_fit_check`.
+struct GasFitCandidate {
+    missed_tx: MissedTransaction,
+    gas: U256,
+}
+
+/// Decide which fee-eligible candidates would actually have fit in the
+/// block, by greedily packing them into the block's unused gas in
+/// descending order of effective priority fee (the same tip `get_tip`
+/// computes). A candidate that fits is a genuine miss; one that doesn't is
+/// reclassified as `NotEnoughSpace`, since a higher-paying transaction would
+/// have taken its place rather than it being censored.
+fn apply_gas_fit_check(
+    mut candidates: Vec<GasFitCandidate>,
+    exec: &ExecutionPayload<Transaction>,
+    missing_txs: &mut HashMap<TxHash, MissedTransaction>,
+    non_inclusion_reasons: &mut HashMap<NonInclusionReason, usize>,
+) {
+    let mut remaining_gas = U256::from((exec.gas_limit - exec.gas_used).as_u64());
+    candidates.sort_by(|a, b| b.missed_tx.tip.cmp(&a.missed_tx.tip));
+    for candidate in candidates {
+        if candidate.gas <= remaining_gas {
+            remaining_gas -= candidate.gas;
+            missing_txs.insert(candidate.missed_tx.hash, candidate.missed_tx);
+        } else {
+            *non_inclusion_reasons
+                .entry(NonInclusionReason::NotEnoughSpace)
+                .or_insert(0) += 1;
+        }
+    }
+}
+
 impl Analysis {
     pub fn summary(&self) -> String {
         format!(
             "Analysis for block {beacon_block}: {included} txs from pool included, {missing} \
              missed, {in_pool} in pool, {in_block} in block, {quorum_not_reached} quorum not \
              reached, {still_propagating} still propagating, {only_hash} only hash known, \
-             {replaced} replaced, {nonce_mismatch} nonce mismatch, {not_enough_space} not enough \
-             space, {base_fee_too_low} base fee too low, {tip_too_low} tip too low, took \
-             {duration:.1}s",
+             {replaced} replaced, {suspicious} suspicious replacements, {nonce_too_low} nonce \
+             too low, {nonce_gap} nonce gap, {not_enough_space} not enough space, \
+             {not_enough_blob_space} not enough blob space, {base_fee_too_low} base fee too low, \
+             {blob_base_fee_too_low} blob base fee too low, {tip_too_low} tip too low, \
+             {sender_has_code} sender has code, took {duration:.1}s",
             beacon_block = self.beacon_block,
             included = self.included_transactions.len(),
             missing = self.missing_transactions.len(),
@@ -262,22 +567,39 @@ impl Analysis {
             still_propagating = self.num_still_propagating,
             only_hash = self.num_only_tx_hash,
             replaced = self.num_replaced_txs,
-            nonce_mismatch = self
+            suspicious = self.num_suspicious_replacements,
+            nonce_too_low = self
                 .non_inclusion_reasons
-                .get(&NonInclusionReason::NonceMismatch)
+                .get(&NonInclusionReason::NonceTooLow)
+                .unwrap_or(&0),
+            nonce_gap = self
+                .non_inclusion_reasons
+                .get(&NonInclusionReason::NonceGap)
                 .unwrap_or(&0),
             not_enough_space = self
                 .non_inclusion_reasons
                 .get(&NonInclusionReason::NotEnoughSpace)
                 .unwrap_or(&0),
+            not_enough_blob_space = self
+                .non_inclusion_reasons
+                .get(&NonInclusionReason::NotEnoughBlobSpace)
+                .unwrap_or(&0),
             base_fee_too_low = self
                 .non_inclusion_reasons
                 .get(&NonInclusionReason::BaseFeeTooLow)
                 .unwrap_or(&0),
+            blob_base_fee_too_low = self
+                .non_inclusion_reasons
+                .get(&NonInclusionReason::BlobBaseFeeTooLow)
+                .unwrap_or(&0),
             tip_too_low = self
                 .non_inclusion_reasons
                 .get(&NonInclusionReason::TipTooLow)
                 .unwrap_or(&0),
+            sender_has_code = self
+                .non_inclusion_reasons
+                .get(&NonInclusionReason::SenderHasCode)
+                .unwrap_or(&0),
             duration = self.duration.as_secs_f64(),
         )
     }
@@ -287,14 +609,22 @@ pub async fn analyze(
     beacon_block: &BeaconBlock<Transaction>,
     pool: &Pool,
     nonce_cache: &mut NonceCache,
+    code_cache: &mut CodeCache,
     quorum: usize,
     propagation_time: chrono::Duration,
-) -> Result<Analysis, NonceCacheError> {
+    genesis_time_seconds: i64,
+    seconds_per_slot: u64,
+    tip_percentile: u64,
+) -> Result<Analysis, AnalyzeError> {
     let start_time = Instant::now();
 
     let exec = &beacon_block.body.execution_payload;
     let mut txs_in_block: HashSet<&TxHash> = HashSet::new();
-    let mut senders_in_block: HashSet<Address> = HashSet::new();
+    // Keyed by (sender, nonce) rather than sender alone: a sender having any
+    // transaction in the block doesn't mean a pool transaction of theirs at a
+    // different nonce was replaced, just that an earlier nonce went through
+    // while this one is still pending.
+    let mut in_block_by_sender_nonce: HashMap<(Address, U256), &Transaction> = HashMap::new();
     for tx in &exec.transactions {
         txs_in_block.insert(&tx.hash);
         let sender = tx.recover_from();
@@ -303,11 +633,11 @@ pub async fn analyze(
                 log::warn!("failed to recover sender address of tx {}: {e}", tx.hash);
             }
             Ok(sender) => {
-                senders_in_block.insert(sender);
+                in_block_by_sender_nonce.insert((sender, tx.nonce), tx);
             }
         }
     }
-    let proposal_time = beacon_block.proposal_time();
+    let proposal_time = beacon_block.proposal_time(genesis_time_seconds, seconds_per_slot);
     let pool_at_t = pool.content_at(proposal_time);
 
     let num_txs_in_block = exec.transactions.len();
@@ -318,8 +648,10 @@ pub async fn analyze(
     let mut num_quorum_not_reached = 0;
     let mut num_still_propagating = 0;
     let mut num_replaced_txs = 0;
+    let mut num_suspicious_replacements = 0;
     let mut missing_txs = HashMap::new();
     let mut non_inclusion_reasons = HashMap::new();
+    let mut gas_fit_candidates = Vec::new();
 
     for (hash, obs_tx) in pool_at_t {
         if txs_in_block.contains(&hash) {
@@ -336,7 +668,9 @@ pub async fn analyze(
         let quorum_reached = obs_tx
             .quorum_reached_timestamp(quorum)
             .expect("quorum has been reached");
-        if beacon_block.proposal_time() - quorum_reached <= propagation_time {
+        if beacon_block.proposal_time(genesis_time_seconds, seconds_per_slot) - quorum_reached
+            <= propagation_time
+        {
             num_still_propagating += 1;
             continue;
         }
@@ -345,16 +679,35 @@ pub async fn analyze(
             continue;
         }
         let tx = obs_tx.transaction.as_ref().unwrap();
-        if let Ok(from) = tx.recover_from() {
-            if senders_in_block.contains(&from) {
-                num_replaced_txs += 1;
-                continue;
+        match tx.recover_from() {
+            Ok(from) => {
+                if let Some(&in_block_tx) = in_block_by_sender_nonce.get(&(from, tx.nonce)) {
+                    match is_legitimate_replacement(tx, in_block_tx, exec.base_fee_per_gas) {
+                        Ok(true) => {
+                            num_replaced_txs += 1;
+                        }
+                        Ok(false) => {
+                            num_suspicious_replacements += 1;
+                        }
+                        Err(e) => {
+                            log::warn!(
+                                "failed to check replacement fee bump for tx {}: {} (tx: {:?})",
+                                tx.hash,
+                                e,
+                                tx,
+                            );
+                            num_replaced_txs += 1;
+                        }
+                    }
+                    continue;
+                }
+            }
+            Err(e) => {
+                log::warn!("failed to recover sender address of tx {}: {e}", tx.hash);
             }
-        } else {
-            log::warn!("failed to recover sender address of tx {}", tx.hash);
         }
 
-        match check_inclusion(tx, beacon_block, nonce_cache).await {
+        match check_inclusion(tx, beacon_block, nonce_cache, code_cache, tip_percentile).await {
             Ok(Some(reason)) => *non_inclusion_reasons.entry(reason).or_insert(0) += 1,
             Ok(None) => {
                 if obs_tx.transaction.is_none() {
@@ -375,6 +728,7 @@ pub async fn analyze(
                     log::warn!("ignoring tx with huge tip");
                     continue;
                 }
+                let gas = tx.gas;
                 let tip = tip.as_u64() as i64;
                 let missed_tx = MissedTransaction {
                     hash: obs_tx.hash,
@@ -383,7 +737,7 @@ pub async fn analyze(
                     quorum_reached,
                     tip,
                 };
-                missing_txs.insert(hash, missed_tx);
+                gas_fit_candidates.push(GasFitCandidate { missed_tx, gas });
             }
             Err(InclusionCheckError::TransactionError(e)) => {
                 log::warn!(
@@ -394,11 +748,16 @@ pub async fn analyze(
                 )
             }
             Err(InclusionCheckError::NonceCacheError(e)) => {
-                return Err(e);
+                return Err(e.into());
+            }
+            Err(InclusionCheckError::CodeCacheError(e)) => {
+                return Err(e.into());
             }
         }
     }
 
+    apply_gas_fit_check(gas_fit_candidates, exec, &mut missing_txs, &mut non_inclusion_reasons);
+
     let duration = start_time.elapsed();
     metrics::ANALYSIS_DURATION.set(duration.as_millis() as f64 / 1000.);
     metrics::TRANSACTIONS_IN_BLOCKS.inc_by(txs_in_block.len() as u64);
@@ -408,6 +767,7 @@ pub async fn analyze(
     metrics::STILL_PROPAGATING_TRANSACTIONS.inc_by(num_still_propagating as u64);
     metrics::ONLY_HASH_TRANSACTIONS.inc_by(num_only_tx_hash as u64);
     metrics::REPLACED_TRANSACTIONS.inc_by(num_replaced_txs as u64);
+    metrics::SUSPICIOUS_REPLACEMENT_TRANSACTIONS.inc_by(num_suspicious_replacements as u64);
     metrics::NOT_ENOUGH_SPACE_TRANSACTIONS.inc_by(
         *non_inclusion_reasons
             .get(&NonInclusionReason::NotEnoughSpace)
@@ -423,9 +783,19 @@ pub async fn analyze(
             .get(&NonInclusionReason::TipTooLow)
             .unwrap_or(&0) as u64,
     );
-    metrics::NONCE_MISMATCH_TRANSACTIONS.inc_by(
+    metrics::NONCE_TOO_LOW_TRANSACTIONS.inc_by(
+        *non_inclusion_reasons
+            .get(&NonInclusionReason::NonceTooLow)
+            .unwrap_or(&0) as u64,
+    );
+    metrics::NONCE_GAP_TRANSACTIONS.inc_by(
+        *non_inclusion_reasons
+            .get(&NonInclusionReason::NonceGap)
+            .unwrap_or(&0) as u64,
+    );
+    metrics::SENDER_HAS_CODE_TRANSACTIONS.inc_by(
         *non_inclusion_reasons
-            .get(&NonInclusionReason::NonceMismatch)
+            .get(&NonInclusionReason::SenderHasCode)
             .unwrap_or(&0) as u64,
     );
     metrics::MISSING_TRANSACTIONS.inc_by(missing_txs.len() as u64);
@@ -441,6 +811,7 @@ pub async fn analyze(
         num_txs_in_pool,
         num_only_tx_hash,
         num_replaced_txs,
+        num_suspicious_replacements,
         non_inclusion_reasons,
         duration,
     })
@@ -0,0 +1,85 @@
+use actix_web::{post, web, Error, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    query_blocks_response, query_misses_response, query_txs_response, AppState, Block,
+    GroupedMissArgs, ItemizedResponse, Miss, MissArgs, RequestError, Tx,
+};
+
+/// A single sub-query within a `/v0/batch` request, tagged by which
+/// endpoint's filter shape and grouping it targets.
+#[derive(Deserialize)]
+#[serde(tag = "target", rename_all = "snake_case")]
+pub enum BatchQuery {
+    Misses(MissArgs),
+    Txs(GroupedMissArgs),
+    Blocks(GroupedMissArgs),
+}
+
+/// The result of one sub-query, tagged the same way as the request so
+/// clients can match responses back to the query that produced them.
+#[derive(Serialize)]
+#[serde(tag = "target", rename_all = "snake_case")]
+pub enum BatchResponseItem {
+    Misses {
+        #[serde(flatten)]
+        response: ItemizedResponse<Miss>,
+    },
+    Txs {
+        #[serde(flatten)]
+        response: ItemizedResponse<Tx>,
+    },
+    Blocks {
+        #[serde(flatten)]
+        response: ItemizedResponse<Block>,
+    },
+}
+
+/// Run several independent miss/tx/block queries in one request, so a
+/// dashboard comparing e.g. several proposers or senders side-by-side can
+/// issue one HTTP call instead of N. Each sub-query is run as if it had hit
+/// the corresponding single-query endpoint, including the per-response
+/// `api_max_response_rows` limit; the number of sub-queries is capped by
+/// `api_batch_max_queries`.
+#[post("/v0/batch")]
+pub async fn handle_batch(
+    data: web::Data<AppState>,
+    queries: web::Json<Vec<BatchQuery>>,
+) -> Result<web::Json<Vec<BatchResponseItem>>, Error> {
+    let queries = queries.into_inner();
+    if queries.len() > data.config.api_batch_max_queries {
+        return Err(Error::from(RequestError::BatchTooLarge {
+            actual: queries.len(),
+            max: data.config.api_batch_max_queries,
+        }));
+    }
+
+    let mut responses = Vec::with_capacity(queries.len());
+    for query in queries {
+        responses.push(run_batch_query(query, &data).await?);
+    }
+    Ok(web::Json(responses))
+}
+
+async fn run_batch_query(
+    query: BatchQuery,
+    data: &web::Data<AppState>,
+) -> Result<BatchResponseItem, Error> {
+    let limit = data.config.api_max_response_rows;
+    let request_time = data.request_time;
+
+    match query {
+        BatchQuery::Misses(args) => {
+            let response = query_misses_response(&args, &data.pool, limit, request_time).await?;
+            Ok(BatchResponseItem::Misses { response })
+        }
+        BatchQuery::Txs(args) => {
+            let response = query_txs_response(&args, &data.pool, limit, request_time).await?;
+            Ok(BatchResponseItem::Txs { response })
+        }
+        BatchQuery::Blocks(args) => {
+            let response = query_blocks_response(&args, &data.pool, limit, request_time).await?;
+            Ok(BatchResponseItem::Blocks { response })
+        }
+    }
+}
@@ -5,7 +5,7 @@ use serde::Serialize;
 
 use super::Miss;
 
-#[derive(Serialize, Clone, PartialEq, Eq)]
+#[derive(Serialize, Clone, PartialEq, Eq, async_graphql::SimpleObject)]
 pub struct Block {
     pub block_hash: String,
     pub slot: i32,
@@ -14,10 +14,11 @@ pub struct Block {
     pub proposal_time: NaiveDateTime,
     pub proposer_index: i32,
     pub num_misses: usize,
+    #[graphql(name = "txs")]
     pub misses: Vec<BlockMiss>,
 }
 
-#[derive(Serialize, Clone, PartialEq, Eq)]
+#[derive(Serialize, Clone, PartialEq, Eq, async_graphql::SimpleObject)]
 pub struct BlockMiss {
     pub tx_hash: String,
     #[serde(with = "ts_seconds")]
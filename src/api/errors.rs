@@ -11,6 +11,10 @@ impl ResponseError for InternalError {}
 pub enum RequestError {
     #[error("Query parameter {parameter} is out of range")]
     ParameterOutOfRange { parameter: String },
+    #[error("Unknown non-inclusion reason {reason}")]
+    InvalidReason { reason: String },
+    #[error("batch of {actual} queries exceeds the maximum of {max}")]
+    BatchTooLarge { actual: usize, max: usize },
 }
 
 impl ResponseError for RequestError {
@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use actix_web::{
+    get,
+    http::header,
+    web::{self, Bytes, Query},
+    Error, HttpResponse, Responder, Result,
+};
+use futures::stream::{self, StreamExt};
+use serde::{de::Error as _, Deserialize, Deserializer};
+use tokio_stream::wrappers::BroadcastStream;
+
+use super::{
+    sse::{keep_alive, to_sse_event},
+    AppState,
+};
+
+/// How often to emit an SSE keep-alive comment so idle connections aren't
+/// closed by intermediate proxies.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Event topics a client can subscribe to via `/v1/events`. Only `miss` is
+/// published today; the enum exists so future event types (e.g. reorgs)
+/// can be added to the same endpoint without a new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topic {
+    Miss,
+}
+
+#[derive(Deserialize)]
+pub struct EventsArgs {
+    #[serde(deserialize_with = "deserialize_topics")]
+    topics: Vec<Topic>,
+}
+
+fn deserialize_topics<'de, D>(d: D) -> Result<Vec<Topic>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(d)?;
+    s.split(',')
+        .map(|t| match t.trim() {
+            "miss" => Ok(Topic::Miss),
+            other => Err(D::Error::custom(format!("unknown topic \"{}\"", other))),
+        })
+        .collect()
+}
+
+/// Stream live censorship events as Server-Sent Events, filtered by the
+/// `topics` query param (currently only `miss` is supported), so dashboards
+/// can subscribe instead of polling `/v0/misses`. Unlike
+/// `/v0/misses/stream`, this doesn't backfill from storage on connect: it
+/// only emits misses detected after the subscription starts.
+#[get("/v1/events")]
+pub async fn handle_events(
+    data: web::Data<AppState>,
+    q: Query<EventsArgs>,
+) -> Result<impl Responder, Error> {
+    if !q.topics.contains(&Topic::Miss) {
+        return Ok(HttpResponse::Ok()
+            .insert_header((header::CONTENT_TYPE, "text/event-stream"))
+            .streaming(stream::empty::<Result<Bytes, Error>>()));
+    }
+
+    let live = BroadcastStream::new(data.miss_feed.subscribe()).filter_map(|r| async move { r.ok() });
+    let events = live.map(|miss| to_sse_event(&miss));
+
+    let body = stream::select(events, keep_alive(KEEP_ALIVE_INTERVAL));
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CONTENT_TYPE, "text/event-stream"))
+        .streaming(body))
+}
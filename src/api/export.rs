@@ -0,0 +1,96 @@
+use actix_web::{
+    get,
+    web::{self, Bytes, Query},
+    Error, HttpResponse, Responder, Result,
+};
+use futures::stream::{self, StreamExt};
+use serde::Deserialize;
+
+use super::{query_misses_stream, AppState, InternalError, Miss, MissArgs};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+#[derive(Deserialize)]
+pub struct ExportArgs {
+    #[serde(flatten)]
+    miss_args: MissArgs,
+    format: ExportFormat,
+}
+
+const CSV_HEADER: &[u8] = b"tx_hash,block_hash,slot,block_number,proposal_time,proposer_index,tx_first_seen,tx_quorum_reached,sender,tip\n";
+
+/// Stream misses matching the same filters as `/v0/misses` as `format=csv`
+/// or `format=ndjson`, writing rows to the response body as they come off
+/// the db cursor instead of materializing the whole result set. Unlike
+/// `/v0/misses`, this isn't bounded by `api_max_response_rows`, so it's the
+/// way to pull weeks of censorship data into pandas or a spreadsheet.
+#[get("/v0/export")]
+pub async fn handle_export(
+    data: web::Data<AppState>,
+    q: Query<ExportArgs>,
+) -> Result<impl Responder, Error> {
+    let ExportArgs { miss_args, format } = q.into_inner();
+    let rows = query_misses_stream(&miss_args, &data.pool, data.request_time)?;
+
+    let rows = rows.map(move |row| match row {
+        Ok(miss) => to_row(&miss, format),
+        Err(e) => {
+            log::error!("error streaming miss export: {}", e);
+            Err(Error::from(InternalError {}))
+        }
+    });
+
+    let body = match format {
+        ExportFormat::Csv => {
+            stream::once(async { Ok(Bytes::from_static(CSV_HEADER)) })
+                .chain(rows)
+                .boxed_local()
+        }
+        ExportFormat::Ndjson => rows.boxed_local(),
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type(content_type(format))
+        .streaming(body))
+}
+
+fn content_type(format: ExportFormat) -> &'static str {
+    match format {
+        ExportFormat::Csv => "text/csv",
+        ExportFormat::Ndjson => "application/x-ndjson",
+    }
+}
+
+fn to_row(miss: &Miss, format: ExportFormat) -> Result<Bytes, Error> {
+    match format {
+        ExportFormat::Csv => Ok(to_csv_row(miss)),
+        ExportFormat::Ndjson => to_ndjson_row(miss),
+    }
+}
+
+fn to_csv_row(miss: &Miss) -> Bytes {
+    Bytes::from(format!(
+        "{},{},{},{},{},{},{},{},{},{}\n",
+        miss.tx_hash,
+        miss.block_hash,
+        miss.slot,
+        miss.block_number,
+        miss.proposal_time.timestamp(),
+        miss.proposer_index,
+        miss.tx_first_seen.timestamp(),
+        miss.tx_quorum_reached.timestamp(),
+        miss.sender,
+        miss.tip.map(|t| t.to_string()).unwrap_or_default(),
+    ))
+}
+
+fn to_ndjson_row(miss: &Miss) -> Result<Bytes, Error> {
+    let mut line = serde_json::to_string(miss).map_err(|_| Error::from(InternalError {}))?;
+    line.push('\n');
+    Ok(Bytes::from(line))
+}
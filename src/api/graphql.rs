@@ -0,0 +1,246 @@
+use std::str::FromStr;
+
+use async_graphql::{Context, EmptyMutation, EmptySubscription, InputObject, Object, Schema, SimpleObject};
+use chrono::Utc;
+use itertools::Itertools;
+
+use crate::{cli::Config, db};
+
+use super::{
+    get_end_bound, group_misses_to_blocks, group_misses_to_txs, is_query_complete, query_misses,
+    query_misses_for_blocks, query_misses_for_txs, Block, GroupedMissArgs, Miss, MissArgs,
+    TimeQuery, Tx,
+};
+
+pub type ApiSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Build the GraphQL schema served at `/v0/graphql`. Built once in
+/// `serve_api` and shared across workers, the same way `poll_new_misses` and
+/// `poll_metrics` share their state.
+pub fn build_schema(pool: db::Pool, config: Config) -> ApiSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(pool)
+        .data(config)
+        .finish()
+}
+
+/// Filter arguments mirroring `MissArgs`/`GroupedMissArgs`: a time range plus
+/// the same censorship filters the REST endpoints accept. `min_num_misses`
+/// only has an effect on the `txs`/`blocks` queries, which group misses
+/// before returning them.
+#[derive(InputObject, Default, Clone)]
+pub struct MissFilter {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub block_number: Option<i32>,
+    pub proposer_index: Option<i32>,
+    pub sender: Option<String>,
+    pub propagation_time: Option<i64>,
+    pub min_tip: Option<i64>,
+    pub min_num_misses: Option<i64>,
+    pub reason: Option<String>,
+    pub exclude_reason: Option<String>,
+}
+
+impl MissFilter {
+    fn parse_time(s: &Option<String>) -> async_graphql::Result<Option<TimeQuery>> {
+        s.as_deref()
+            .map(TimeQuery::from_str)
+            .transpose()
+            .map_err(|e| async_graphql::Error::new(e.to_string()))
+    }
+
+    fn into_miss_args(&self) -> async_graphql::Result<MissArgs> {
+        Ok(MissArgs::new(
+            Self::parse_time(&self.from)?,
+            Self::parse_time(&self.to)?,
+            self.block_number,
+            self.proposer_index,
+            self.sender.clone(),
+            self.propagation_time,
+            self.min_tip,
+            self.reason.clone(),
+            self.exclude_reason.clone(),
+        ))
+    }
+
+    fn into_grouped_args(&self) -> async_graphql::Result<GroupedMissArgs> {
+        Ok(GroupedMissArgs::new(
+            Self::parse_time(&self.from)?,
+            Self::parse_time(&self.to)?,
+            self.block_number,
+            self.proposer_index,
+            self.sender.clone(),
+            self.propagation_time,
+            self.min_tip,
+            self.min_num_misses,
+            self.reason.clone(),
+            self.exclude_reason.clone(),
+        ))
+    }
+}
+
+/// Connection-style pagination info, mirroring the `complete`/end-bound
+/// cursor the REST endpoints return alongside their `items`.
+#[derive(SimpleObject)]
+pub struct PageInfo {
+    pub has_more: bool,
+    pub end_cursor: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct MissConnection {
+    pub items: Vec<Miss>,
+    pub page_info: PageInfo,
+}
+
+#[derive(SimpleObject)]
+pub struct TxConnection {
+    pub items: Vec<Tx>,
+    pub page_info: PageInfo,
+}
+
+#[derive(SimpleObject)]
+pub struct BlockConnection {
+    pub items: Vec<Block>,
+    pub page_info: PageInfo,
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Misses matching `filter`, each carrying its own transaction and block
+    /// details. Mirrors the `/v0/misses` REST endpoint.
+    async fn misses(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<MissFilter>,
+    ) -> async_graphql::Result<MissConnection> {
+        let pool = ctx.data::<db::Pool>()?;
+        let config = ctx.data::<Config>()?;
+        let request_time = Utc::now().naive_utc();
+
+        let args = filter.unwrap_or_default().into_miss_args()?;
+        let misses = query_misses(&args, pool, config.api_max_response_rows, request_time)
+            .await
+            .map_err(|e| async_graphql::Error::new(e.to_string()))?;
+
+        let complete = is_query_complete(&misses, config.api_max_response_rows);
+        let end_cursor = get_end_bound(&misses).map(|b| b.to_string());
+
+        Ok(MissConnection {
+            items: misses,
+            page_info: PageInfo {
+                has_more: !complete,
+                end_cursor,
+            },
+        })
+    }
+
+    /// Misses grouped by transaction, with each transaction's misses nested
+    /// as `blocks`. Mirrors the `/v0/txs` REST endpoint.
+    async fn txs(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<MissFilter>,
+    ) -> async_graphql::Result<TxConnection> {
+        let pool = ctx.data::<db::Pool>()?;
+        let config = ctx.data::<Config>()?;
+        let request_time = Utc::now().naive_utc();
+
+        let grouped_args = filter.unwrap_or_default().into_grouped_args()?;
+        let min_num_misses = grouped_args
+            .checked_min_num_misses()
+            .map_err(|e| async_graphql::Error::new(e.to_string()))?;
+        let miss_args: MissArgs = grouped_args.clone().into();
+
+        let misses = query_misses_for_txs(
+            &grouped_args,
+            pool,
+            config.api_max_response_rows,
+            request_time,
+        )
+        .await
+        .map_err(|e| async_graphql::Error::new(e.to_string()))?;
+        let misses: Vec<Miss> = misses.into_iter().unique().collect();
+
+        let complete = is_query_complete(&misses, config.api_max_response_rows);
+        let end_cursor = get_end_bound(&misses).map(|b| b.to_string());
+
+        let mut txs: Vec<Tx> = group_misses_to_txs(&misses)
+            .iter()
+            .filter(|tx| min_num_misses.is_none() || tx.num_misses as i64 >= min_num_misses.unwrap())
+            .cloned()
+            .collect();
+        txs.sort();
+        let ascending = miss_args
+            .checked_is_order_ascending(request_time)
+            .map_err(|e| async_graphql::Error::new(e.to_string()))?;
+        if !ascending {
+            txs.reverse();
+        }
+
+        Ok(TxConnection {
+            items: txs,
+            page_info: PageInfo {
+                has_more: !complete,
+                end_cursor,
+            },
+        })
+    }
+
+    /// Misses grouped by block, with each block's misses nested as `txs`.
+    /// Mirrors the `/v0/blocks` REST endpoint.
+    async fn blocks(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<MissFilter>,
+    ) -> async_graphql::Result<BlockConnection> {
+        let pool = ctx.data::<db::Pool>()?;
+        let config = ctx.data::<Config>()?;
+        let request_time = Utc::now().naive_utc();
+
+        let grouped_args = filter.unwrap_or_default().into_grouped_args()?;
+        let min_num_misses = grouped_args
+            .checked_min_num_misses()
+            .map_err(|e| async_graphql::Error::new(e.to_string()))?;
+        let miss_args: MissArgs = grouped_args.clone().into();
+
+        let misses = query_misses_for_blocks(
+            &grouped_args,
+            pool,
+            config.api_max_response_rows,
+            request_time,
+        )
+        .await
+        .map_err(|e| async_graphql::Error::new(e.to_string()))?;
+        let misses: Vec<Miss> = misses.into_iter().unique().collect();
+
+        let complete = is_query_complete(&misses, config.api_max_response_rows);
+        let end_cursor = get_end_bound(&misses).map(|b| b.to_string());
+
+        let mut blocks: Vec<Block> = group_misses_to_blocks(&misses)
+            .iter()
+            .filter(|block| {
+                min_num_misses.is_none() || block.num_misses as i64 >= min_num_misses.unwrap()
+            })
+            .cloned()
+            .collect();
+        blocks.sort();
+        let ascending = miss_args
+            .checked_is_order_ascending(request_time)
+            .map_err(|e| async_graphql::Error::new(e.to_string()))?;
+        if !ascending {
+            blocks.reverse();
+        }
+
+        Ok(BlockConnection {
+            items: blocks,
+            page_info: PageInfo {
+                has_more: !complete,
+                end_cursor,
+            },
+        })
+    }
+}
@@ -1,14 +1,13 @@
 use actix_web::{
-    get,
+    get, post,
     web::{self, Json, Query},
-    Error, Responder, Result,
+    Error, HttpResponse, Responder, Result,
 };
-use itertools::Itertools;
+use async_graphql_actix_web::{GraphQLRequest, GraphQLResponse};
 
 use super::{
-    get_end_bound, group_misses_to_blocks, group_misses_to_txs, is_query_complete, query_misses,
-    query_misses_for_blocks, query_misses_for_txs, AppState, Block, GroupedMissArgs,
-    ItemizedResponse, Miss, MissArgs, Tx,
+    query_blocks_response, query_misses_response, query_txs_response, AppState, GroupedMissArgs,
+    MissArgs,
 };
 
 #[get("/v0/misses")]
@@ -16,18 +15,13 @@ pub async fn handle_misses(
     data: web::Data<AppState>,
     q: Query<MissArgs>,
 ) -> Result<impl Responder, Error> {
-    let misses = query_misses(&q.0, &data).await?;
-
-    let complete = is_query_complete(&misses, data.config.api_max_response_rows);
-    let data_to = get_end_bound(&misses, &q.0.checked_from()?);
-
-    let response = ItemizedResponse::new(
-        misses,
-        complete,
-        q.0.checked_from()?,
-        q.0.checked_to(data.request_time)?,
-        data_to,
-    );
+    let response = query_misses_response(
+        &q.0,
+        &data.pool,
+        data.config.api_max_response_rows,
+        data.request_time,
+    )
+    .await?;
     Ok(Json(response))
 }
 
@@ -36,33 +30,35 @@ pub async fn handle_txs(
     data: web::Data<AppState>,
     q: Query<GroupedMissArgs>,
 ) -> Result<impl Responder, Error> {
-    let misses = query_misses_for_txs(&q.0, &data).await?;
-    let misses: Vec<Miss> = misses.into_iter().unique().collect();
-
-    let min_num_misses = q.checked_min_num_misses()?;
-    let miss_args: MissArgs = q.0.into();
-
-    let complete = is_query_complete(&misses, data.config.api_max_response_rows);
-    let data_to = get_end_bound(&misses, &miss_args.checked_from()?);
+    let response = query_txs_response(
+        &q.0,
+        &data.pool,
+        data.config.api_max_response_rows,
+        data.request_time,
+    )
+    .await?;
+    Ok(Json(response))
+}
 
-    let mut txs: Vec<Tx> = group_misses_to_txs(&misses)
-        .iter()
-        .filter(|tx| min_num_misses.is_none() || tx.num_misses as i64 >= min_num_misses.unwrap())
-        .cloned()
-        .collect();
-    txs.sort();
-    if !miss_args.checked_is_order_ascending(data.request_time)? {
-        txs.reverse();
-    }
+/// Run a GraphQL query against the same miss/tx/block data as the REST
+/// endpoints, letting clients request exactly the fields they need and
+/// traverse miss→tx and miss→block relationships in a single round trip.
+#[post("/v0/graphql")]
+pub async fn handle_graphql(
+    data: web::Data<AppState>,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    data.schema.execute(request.into_inner()).await.into()
+}
 
-    let response = ItemizedResponse::new(
-        txs,
-        complete,
-        miss_args.checked_from()?,
-        miss_args.checked_to(data.request_time)?,
-        data_to,
-    );
-    Ok(Json(response))
+/// Expose aggregate censorship figures (total misses, misses per proposer,
+/// distinct censored senders, propagation delay distribution) in Prometheus
+/// text exposition format so operators can scrape the API into Grafana.
+#[get("/metrics")]
+pub async fn handle_metrics(data: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(data.metrics.encode())
 }
 
 #[get("/v0/blocks")]
@@ -70,33 +66,12 @@ pub async fn handle_blocks(
     data: web::Data<AppState>,
     q: Query<GroupedMissArgs>,
 ) -> Result<impl Responder, Error> {
-    let misses = query_misses_for_blocks(&q.0, &data).await?;
-    let misses: Vec<Miss> = misses.into_iter().unique().collect();
-
-    let min_num_misses = q.checked_min_num_misses()?;
-    let miss_args: MissArgs = q.0.into();
-
-    let complete = is_query_complete(&misses, data.config.api_max_response_rows);
-    let data_to = get_end_bound(&misses, &miss_args.checked_from()?);
-
-    let mut blocks: Vec<Block> = group_misses_to_blocks(&misses)
-        .iter()
-        .filter(|block| {
-            min_num_misses.is_none() || block.num_misses as i64 >= min_num_misses.unwrap()
-        })
-        .cloned()
-        .collect();
-    blocks.sort();
-    if !miss_args.checked_is_order_ascending(data.request_time)? {
-        blocks.reverse();
-    }
-
-    let response = ItemizedResponse::new(
-        blocks,
-        complete,
-        miss_args.checked_from()?,
-        miss_args.checked_to(data.request_time)?,
-        data_to,
-    );
+    let response = query_blocks_response(
+        &q.0,
+        &data.pool,
+        data.config.api_max_response_rows,
+        data.request_time,
+    )
+    .await?;
     Ok(Json(response))
 }
@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, Utc};
+use prometheus::{Encoder, Histogram, HistogramOpts, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder};
+
+use super::{query_misses_by_proposer, query_propagation_delays_since, query_totals};
+use crate::db::Pool;
+
+/// How often the background task recomputes the aggregate censorship
+/// metrics from storage.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Aggregate censorship figures exposed at `/metrics`, kept in their own
+/// registry (rather than the process-global one `crate::metrics` uses) so
+/// scraping the API doesn't depend on anything the watch process registers.
+#[derive(Clone)]
+pub struct ApiMetrics {
+    registry: Registry,
+    total_misses: IntGauge,
+    misses_by_proposer: IntGaugeVec,
+    distinct_censored_senders: IntGauge,
+    propagation_delay_seconds: Histogram,
+}
+
+impl ApiMetrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let total_misses = IntGauge::new("censorship_total_misses", "Total number of recorded misses")
+            .expect("can create metric");
+        let misses_by_proposer = IntGaugeVec::new(
+            Opts::new(
+                "censorship_misses_by_proposer",
+                "Misses grouped by the block proposer's validator index",
+            ),
+            &["proposer_index"],
+        )
+        .expect("can create metric");
+        let distinct_censored_senders = IntGauge::new(
+            "censorship_distinct_censored_senders",
+            "Number of distinct senders with at least one recorded miss",
+        )
+        .expect("can create metric");
+        let propagation_delay_seconds = Histogram::with_opts(
+            HistogramOpts::new(
+                "censorship_propagation_delay_seconds",
+                "Seconds between a missed transaction reaching quorum and the block that \
+                 should have included it being proposed",
+            )
+            .buckets(vec![1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0, 256.0]),
+        )
+        .expect("can create metric");
+
+        registry
+            .register(Box::new(total_misses.clone()))
+            .expect("can register metric");
+        registry
+            .register(Box::new(misses_by_proposer.clone()))
+            .expect("can register metric");
+        registry
+            .register(Box::new(distinct_censored_senders.clone()))
+            .expect("can register metric");
+        registry
+            .register(Box::new(propagation_delay_seconds.clone()))
+            .expect("can register metric");
+
+        ApiMetrics {
+            registry,
+            total_misses,
+            misses_by_proposer,
+            distinct_censored_senders,
+            propagation_delay_seconds,
+        }
+    }
+
+    /// Render all registered metrics in Prometheus text exposition format.
+    pub fn encode(&self) -> String {
+        let encoder = TextEncoder::new();
+        let mut buffer = Vec::new();
+        encoder
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("can encode metrics");
+        String::from_utf8(buffer).expect("can convert metrics to string")
+    }
+}
+
+impl Default for ApiMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Periodically recompute the aggregate gauges from storage and feed any
+/// newly recorded delays into the propagation histogram, the same way
+/// `poll_new_misses` keeps the live miss feed fresh. This runs once per
+/// process rather than per request so that scraping `/metrics` stays cheap.
+pub async fn poll_metrics(pool: Pool, metrics: ApiMetrics, max_distinct_proposers: usize) {
+    let mut since = Utc::now().naive_utc();
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = refresh(&pool, &metrics, max_distinct_proposers, &mut since).await {
+            log::warn!("error refreshing api metrics: {}", e);
+        }
+    }
+}
+
+async fn refresh(
+    pool: &Pool,
+    metrics: &ApiMetrics,
+    max_distinct_proposers: usize,
+    since: &mut NaiveDateTime,
+) -> Result<(), sqlx::Error> {
+    let totals = query_totals(pool).await?;
+    metrics.total_misses.set(totals.total_misses);
+    metrics.distinct_censored_senders.set(totals.distinct_senders);
+
+    let mut by_proposer = query_misses_by_proposer(pool).await?;
+    by_proposer.sort_by_key(|row| std::cmp::Reverse(row.num_misses));
+    metrics.misses_by_proposer.reset();
+    if by_proposer.len() > max_distinct_proposers {
+        log::warn!(
+            "capping misses_by_proposer cardinality at {} of {} distinct proposers",
+            max_distinct_proposers,
+            by_proposer.len()
+        );
+    }
+    for row in by_proposer.into_iter().take(max_distinct_proposers) {
+        metrics
+            .misses_by_proposer
+            .with_label_values(&[&row.proposer_index.to_string()])
+            .set(row.num_misses);
+    }
+
+    let new_delays = query_propagation_delays_since(pool, *since).await?;
+    if let Some(latest) = new_delays.iter().map(|row| row.proposal_time).max() {
+        *since = latest;
+    }
+    for delay in new_delays {
+        metrics
+            .propagation_delay_seconds
+            .observe(delay.delay_seconds as f64);
+    }
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use std::{str::FromStr, string::ToString};
 
-use chrono::NaiveDateTime;
+use chrono::{Duration, NaiveDateTime, NaiveTime};
 use thiserror::Error;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -30,6 +30,8 @@ pub enum ParseError {
     InvalidProposalTimestamp,
     #[error("invalid tx quorum reached timestamp")]
     InvalidTxQuorumReachedTimestamp,
+    #[error("invalid relative time expression")]
+    InvalidRelativeExpression,
 }
 
 impl FromStr for MissTimeTuple {
@@ -70,6 +72,92 @@ impl FromStr for MissTimeTuple {
     }
 }
 
+/// A `from`/`to` query bound as given by the client, before it is resolved
+/// against the request time. Accepts either an absolute `MissTimeTuple` or a
+/// relative expression (`-1d`, `-15m`, `now`, `today`, `yesterday 17:20`, ...).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TimeQuery {
+    Absolute(MissTimeTuple),
+    Relative(Duration),
+    Now,
+    Today,
+    Yesterday(NaiveTime),
+}
+
+impl TimeQuery {
+    /// Resolve this query against the time the request was received.
+    pub fn resolve(&self, request_time: NaiveDateTime) -> MissTimeTuple {
+        let proposal_time = match self {
+            TimeQuery::Absolute(t) => return *t,
+            TimeQuery::Relative(offset) => request_time + *offset,
+            TimeQuery::Now => request_time,
+            TimeQuery::Today => request_time.date().and_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
+            TimeQuery::Yesterday(time) => {
+                (request_time.date() - Duration::days(1)).and_time(*time)
+            }
+        };
+        MissTimeTuple {
+            proposal_time,
+            tx_quorum_reached: None,
+        }
+    }
+}
+
+impl FromStr for TimeQuery {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        if let Ok(t) = MissTimeTuple::from_str(s) {
+            return Ok(TimeQuery::Absolute(t));
+        }
+        if s == "now" {
+            return Ok(TimeQuery::Now);
+        }
+        if s == "today" {
+            return Ok(TimeQuery::Today);
+        }
+        if let Some(rest) = s.strip_prefix("yesterday") {
+            let rest = rest.trim();
+            let time = if rest.is_empty() {
+                NaiveTime::from_hms_opt(0, 0, 0).unwrap()
+            } else {
+                NaiveTime::parse_from_str(rest, "%H:%M")
+                    .map_err(|_| ParseError::InvalidRelativeExpression)?
+            };
+            return Ok(TimeQuery::Yesterday(time));
+        }
+        parse_relative_offset(s).map(TimeQuery::Relative)
+    }
+}
+
+/// Parse a relative offset such as `-1d`, `-15m`, `+6h`, `-2w`: a required
+/// sign, an integer count, and a unit in {s, m, h, d, w}.
+fn parse_relative_offset(s: &str) -> Result<Duration, ParseError> {
+    let mut chars = s.chars();
+    let sign = match chars.next() {
+        Some('-') => -1,
+        Some('+') => 1,
+        _ => return Err(ParseError::InvalidRelativeExpression),
+    };
+    let rest = &s[1..];
+    if rest.len() < 2 {
+        return Err(ParseError::InvalidRelativeExpression);
+    }
+    let (digits, unit) = rest.split_at(rest.len() - 1);
+    let n: i64 = digits
+        .parse()
+        .map_err(|_| ParseError::InvalidRelativeExpression)?;
+    let seconds_per_unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 86400 * 7,
+        _ => return Err(ParseError::InvalidRelativeExpression),
+    };
+    Ok(Duration::seconds(sign * n * seconds_per_unit))
+}
+
 pub mod serde_miss_time_tuple {
     use std::{fmt, str::FromStr};
 
@@ -159,3 +247,75 @@ pub mod serde_opt_miss_time_tuple {
         }
     }
 }
+
+pub mod serde_opt_time_query {
+    use std::fmt;
+
+    use serde::{de::Visitor, Deserializer, Serializer};
+
+    use super::TimeQuery;
+
+    #[allow(dead_code)]
+    pub fn serialize<S>(opt: &Option<TimeQuery>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match opt {
+            Some(TimeQuery::Absolute(t)) => serializer.serialize_str(t.to_string().as_str()),
+            Some(_) => Err(serde::ser::Error::custom(
+                "cannot serialize an unresolved relative time query",
+            )),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(d: D) -> Result<Option<TimeQuery>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        d.deserialize_option(OptionTimeQueryVisitor)
+    }
+
+    struct OptionTimeQueryVisitor;
+
+    impl<'de> Visitor<'de> for OptionTimeQueryVisitor {
+        type Value = Option<TimeQuery>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("string or none")
+        }
+
+        fn visit_some<D>(self, d: D) -> Result<Self::Value, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            d.deserialize_str(TimeQueryVisitor).map(Some)
+        }
+
+        fn visit_none<E>(self) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(None)
+        }
+    }
+
+    struct TimeQueryVisitor;
+
+    impl<'de> Visitor<'de> for TimeQueryVisitor {
+        type Value = TimeQuery;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an absolute or relative time expression")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            use std::str::FromStr;
+
+            TimeQuery::from_str(v).map_err(serde::de::Error::custom)
+        }
+    }
+}
@@ -12,6 +12,12 @@ use errors::*;
 mod requests;
 use requests::*;
 
+mod block;
+use block::*;
+
+mod tx;
+use tx::*;
+
 mod handlers;
 use handlers::*;
 
@@ -21,27 +27,71 @@ use queries::*;
 mod responses;
 use responses::*;
 
+mod stream;
+use stream::*;
+
+mod sse;
+
+mod metrics;
+use metrics::*;
+
+mod graphql;
+use graphql::*;
+
+mod batch;
+use batch::*;
+
+mod export;
+use export::*;
+
+mod events;
+use events::*;
+
 pub struct AppState {
     config: Config,
     pool: db::Pool,
     request_time: NaiveDateTime,
+    miss_feed: tokio::sync::broadcast::Sender<Miss>,
+    metrics: ApiMetrics,
+    schema: ApiSchema,
 }
 
 pub async fn serve_api(config: Config) -> Result<(), std::io::Error> {
     let pool = db::connect(&config.api_db_connection).await.unwrap();
     let host_and_port = (config.api_host.clone(), config.api_port);
 
+    let miss_feed = new_feed();
+    tokio::spawn(poll_new_misses(pool.clone(), miss_feed.clone()));
+
+    let metrics = ApiMetrics::new();
+    tokio::spawn(poll_metrics(
+        pool.clone(),
+        metrics.clone(),
+        config.api_metrics_max_distinct_proposers,
+    ));
+
+    let schema = build_schema(pool.clone(), config.clone());
+
     HttpServer::new(move || {
         let state = AppState {
             config: config.clone(),
             pool: pool.clone(),
             request_time: Utc::now().naive_utc(),
+            miss_feed: miss_feed.clone(),
+            metrics: metrics.clone(),
+            schema: schema.clone(),
         };
         App::new()
             .app_data(web::Data::new(state))
             .service(handle_misses)
             .service(handle_txs)
             .service(handle_blocks)
+            .service(handle_misses_stream)
+            .service(handle_metrics)
+            .service(handle_graphql)
+            .service(handle_batch)
+            .service(handle_export)
+            .service(handle_events)
     })
     .bind(host_and_port)?
     .run()
@@ -1,14 +1,20 @@
 use std::hash::Hash;
 
-use actix_web::{web, Error, Result};
+use actix_web::Error;
 use chrono::{naive::serde::ts_seconds, NaiveDateTime};
+use itertools::Itertools;
 use serde::Serialize;
 
 use super::{
-    miss_range_bound::MissRangeBound, requests::GroupedMissArgs, AppState, InternalError, MissArgs,
+    block::{group_misses_to_blocks, Block},
+    miss_time_tuple::MissTimeTuple,
+    requests::GroupedMissArgs,
+    responses::ItemizedResponse,
+    tx::{group_misses_to_txs, Tx},
+    InternalError, MissArgs,
 };
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, async_graphql::SimpleObject)]
 pub struct Miss {
     pub tx_hash: String,
     pub block_hash: String,
@@ -24,9 +30,8 @@ pub struct Miss {
     pub sender: String,
     pub tip: Option<i64>,
     #[serde(skip_serializing)]
+    #[graphql(skip)]
     pub filtered_miss_count: i64,
-    #[serde(skip_serializing)]
-    pub filtered_miss_row_by_proposal_time: i64,
 }
 
 impl Hash for Miss {
@@ -44,10 +49,13 @@ impl PartialEq for Miss {
 
 impl Eq for Miss {}
 
-pub async fn query_misses(args: &MissArgs, data: &web::Data<AppState>) -> Result<Vec<Miss>, Error> {
-    let pool = &data.pool;
-    let limit = data.config.api_max_response_rows;
-    let (min, max) = args.checked_time_range(data.request_time)?;
+pub async fn query_misses(
+    args: &MissArgs,
+    pool: &crate::db::Pool,
+    limit: usize,
+    request_time: NaiveDateTime,
+) -> Result<Vec<Miss>, Error> {
+    let (min, max) = args.checked_time_range(request_time)?;
     let result = sqlx::query_file_as!(
         Miss,
         "src/api/misses_query.sql",
@@ -58,9 +66,11 @@ pub async fn query_misses(args: &MissArgs, data: &web::Data<AppState>) -> Result
         args.checked_sender()?,
         args.checked_propagation_time()?,
         args.checked_min_tip()?,
-        args.checked_is_order_ascending(data.request_time)?,
+        args.checked_is_order_ascending(request_time)?,
+        args.checked_reason()?.map(|r| r.to_string()),
+        args.checked_exclude_reason()?.map(|r| r.to_string()),
         limit as i64,
-        args.checked_from()?.offset.unwrap_or(0) as i64,
+        args.checked_from(request_time)?.tx_quorum_reached,
     )
     .fetch_all(pool)
     .await;
@@ -76,12 +86,12 @@ pub async fn query_misses(args: &MissArgs, data: &web::Data<AppState>) -> Result
 
 pub async fn query_misses_for_txs(
     args: &GroupedMissArgs,
-    data: &web::Data<AppState>,
+    pool: &crate::db::Pool,
+    limit: usize,
+    request_time: NaiveDateTime,
 ) -> Result<Vec<Miss>, Error> {
-    let pool = &data.pool;
-    let limit = data.config.api_max_response_rows;
     let miss_args: MissArgs = args.clone().into();
-    let (min, max) = miss_args.checked_time_range(data.request_time)?;
+    let (min, max) = miss_args.checked_time_range(request_time)?;
     let result = sqlx::query_file_as!(
         Miss,
         "src/api/txs_query.sql",
@@ -92,9 +102,11 @@ pub async fn query_misses_for_txs(
         miss_args.checked_sender()?,
         miss_args.checked_propagation_time()?,
         miss_args.checked_min_tip()?,
-        miss_args.checked_is_order_ascending(data.request_time)?,
+        miss_args.checked_is_order_ascending(request_time)?,
+        miss_args.checked_reason()?.map(|r| r.to_string()),
+        miss_args.checked_exclude_reason()?.map(|r| r.to_string()),
         limit as i64,
-        miss_args.checked_from()?.offset.unwrap_or(0) as i64,
+        miss_args.checked_from(request_time)?.tx_quorum_reached,
     )
     .fetch_all(pool)
     .await;
@@ -110,12 +122,12 @@ pub async fn query_misses_for_txs(
 
 pub async fn query_misses_for_blocks(
     args: &GroupedMissArgs,
-    data: &web::Data<AppState>,
+    pool: &crate::db::Pool,
+    limit: usize,
+    request_time: NaiveDateTime,
 ) -> Result<Vec<Miss>, Error> {
-    let pool = &data.pool;
-    let limit = data.config.api_max_response_rows;
     let miss_args: MissArgs = args.clone().into();
-    let (min, max) = miss_args.checked_time_range(data.request_time)?;
+    let (min, max) = miss_args.checked_time_range(request_time)?;
     let result = sqlx::query_file_as!(
         Miss,
         "src/api/blocks_query.sql",
@@ -126,9 +138,11 @@ pub async fn query_misses_for_blocks(
         miss_args.checked_sender()?,
         miss_args.checked_propagation_time()?,
         miss_args.checked_min_tip()?,
-        miss_args.checked_is_order_ascending(data.request_time)?,
+        miss_args.checked_is_order_ascending(request_time)?,
+        miss_args.checked_reason()?.map(|r| r.to_string()),
+        miss_args.checked_exclude_reason()?.map(|r| r.to_string()),
         limit as i64,
-        miss_args.checked_from()?.offset.unwrap_or(0) as i64,
+        miss_args.checked_from(request_time)?.tx_quorum_reached,
     )
     .fetch_all(pool)
     .await;
@@ -142,6 +156,140 @@ pub async fn query_misses_for_blocks(
     }
 }
 
+/// Stream misses matching `args` in ascending order without a row cap or an
+/// in-memory result set, for `/v0/export`'s CSV/NDJSON streaming responses.
+/// Unlike `query_misses`, this never truncates at `api_max_response_rows`:
+/// callers are expected to consume the whole range incrementally.
+pub fn query_misses_stream<'p>(
+    args: &MissArgs,
+    pool: &'p crate::db::Pool,
+    request_time: NaiveDateTime,
+) -> Result<impl futures::Stream<Item = Result<Miss, sqlx::Error>> + 'p, Error> {
+    let (min, max) = args.checked_time_range(request_time)?;
+    Ok(sqlx::query_file_as!(
+        Miss,
+        "src/api/export_misses_query.sql",
+        min,
+        max,
+        args.checked_block_number()?,
+        args.checked_proposer_index()?,
+        args.checked_sender()?,
+        args.checked_propagation_time()?,
+        args.checked_min_tip()?,
+        args.checked_reason()?.map(|r| r.to_string()),
+        args.checked_exclude_reason()?.map(|r| r.to_string()),
+    )
+    .fetch(pool))
+}
+
+/// Fetch misses recorded with a proposal time strictly after `since`, ordered
+/// ascending. Used to feed `/v0/misses/stream` with newly detected misses.
+pub async fn query_misses_since(
+    pool: &crate::db::Pool,
+    since: NaiveDateTime,
+) -> Result<Vec<Miss>, sqlx::Error> {
+    sqlx::query_file_as!(Miss, "src/api/new_misses_query.sql", since)
+        .fetch_all(pool)
+        .await
+}
+
+/// Run `query_misses` and wrap the result in an `ItemizedResponse`, shared by
+/// `handle_misses` and the `/v0/batch` `misses` sub-query so a change to one
+/// can't drift from the other.
+pub async fn query_misses_response(
+    args: &MissArgs,
+    pool: &crate::db::Pool,
+    limit: usize,
+    request_time: NaiveDateTime,
+) -> Result<ItemizedResponse<Miss>, Error> {
+    let misses = query_misses(args, pool, limit, request_time).await?;
+    let complete = is_query_complete(&misses, limit);
+    let data_to = get_end_bound(&misses);
+    Ok(ItemizedResponse::new(
+        misses,
+        complete,
+        args.checked_from(request_time)?,
+        args.checked_to(request_time)?,
+        data_to,
+    ))
+}
+
+/// Run `query_misses_for_txs`, dedupe and group the result into `Tx`es
+/// filtered by `min_num_misses`, and wrap it in an `ItemizedResponse`, shared
+/// by `handle_txs` and the `/v0/batch` `txs` sub-query.
+pub async fn query_txs_response(
+    args: &GroupedMissArgs,
+    pool: &crate::db::Pool,
+    limit: usize,
+    request_time: NaiveDateTime,
+) -> Result<ItemizedResponse<Tx>, Error> {
+    let misses = query_misses_for_txs(args, pool, limit, request_time).await?;
+    let misses: Vec<Miss> = misses.into_iter().unique().collect();
+
+    let min_num_misses = args.checked_min_num_misses()?;
+    let miss_args: MissArgs = args.clone().into();
+
+    let complete = is_query_complete(&misses, limit);
+    let data_to = get_end_bound(&misses);
+
+    let mut txs: Vec<Tx> = group_misses_to_txs(&misses)
+        .iter()
+        .filter(|tx| min_num_misses.is_none() || tx.num_misses as i64 >= min_num_misses.unwrap())
+        .cloned()
+        .collect();
+    txs.sort();
+    if !miss_args.checked_is_order_ascending(request_time)? {
+        txs.reverse();
+    }
+
+    Ok(ItemizedResponse::new(
+        txs,
+        complete,
+        miss_args.checked_from(request_time)?,
+        miss_args.checked_to(request_time)?,
+        data_to,
+    ))
+}
+
+/// Run `query_misses_for_blocks`, dedupe and group the result into `Block`s
+/// filtered by `min_num_misses`, and wrap it in an `ItemizedResponse`, shared
+/// by `handle_blocks` and the `/v0/batch` `blocks` sub-query.
+pub async fn query_blocks_response(
+    args: &GroupedMissArgs,
+    pool: &crate::db::Pool,
+    limit: usize,
+    request_time: NaiveDateTime,
+) -> Result<ItemizedResponse<Block>, Error> {
+    let misses = query_misses_for_blocks(args, pool, limit, request_time).await?;
+    let misses: Vec<Miss> = misses.into_iter().unique().collect();
+
+    let min_num_misses = args.checked_min_num_misses()?;
+    let miss_args: MissArgs = args.clone().into();
+
+    let complete = is_query_complete(&misses, limit);
+    let data_to = get_end_bound(&misses);
+
+    let mut blocks: Vec<Block> = group_misses_to_blocks(&misses)
+        .iter()
+        .filter(|block| {
+            min_num_misses.is_none() || block.num_misses as i64 >= min_num_misses.unwrap()
+        })
+        .cloned()
+        .collect();
+    blocks.sort();
+    if !miss_args.checked_is_order_ascending(request_time)? {
+        blocks.reverse();
+    }
+
+    Ok(ItemizedResponse::new(
+        blocks,
+        complete,
+        miss_args.checked_from(request_time)?,
+        miss_args.checked_to(request_time)?,
+        data_to,
+    ))
+}
+
 pub fn is_query_complete(misses: &[Miss], limit: usize) -> bool {
     let filtered_miss_count = misses
         .get(0)
@@ -150,17 +298,67 @@ pub fn is_query_complete(misses: &[Miss], limit: usize) -> bool {
     filtered_miss_count < limit as i64
 }
 
-pub fn get_end_bound(misses: &[Miss], query_from: &MissRangeBound) -> Option<MissRangeBound> {
-    misses.last().map(|last_miss| {
-        let offset_inclusive = (last_miss.filtered_miss_row_by_proposal_time as usize)
-            + if last_miss.proposal_time == query_from.proposal_time {
-                query_from.offset.unwrap_or(0)
-            } else {
-                0
-            };
-        MissRangeBound {
-            proposal_time: last_miss.proposal_time,
-            offset: Some(offset_inclusive + 1),
-        }
+/// The cursor a client should pass as `from` to continue a paginated query
+/// right after the last returned miss, disambiguating misses that share a
+/// `proposal_time` by `tx_quorum_reached` rather than a row offset.
+pub fn get_end_bound(misses: &[Miss]) -> Option<MissTimeTuple> {
+    misses.last().map(|last_miss| MissTimeTuple {
+        proposal_time: last_miss.proposal_time,
+        tx_quorum_reached: Some(last_miss.tx_quorum_reached),
     })
 }
+
+#[derive(Debug)]
+pub struct MissTotals {
+    pub total_misses: i64,
+    pub distinct_senders: i64,
+}
+
+/// Fetch the total number of recorded misses and the number of distinct
+/// senders among them, for the `/metrics` totals and gauges.
+pub async fn query_totals(pool: &crate::db::Pool) -> Result<MissTotals, sqlx::Error> {
+    sqlx::query_file_as!(MissTotals, "src/api/metrics_totals_query.sql")
+        .fetch_one(pool)
+        .await
+}
+
+#[derive(Debug)]
+pub struct MissesByProposer {
+    pub proposer_index: i32,
+    pub num_misses: i64,
+}
+
+/// Fetch misses grouped by the proposer index of the block that missed them,
+/// for the `/metrics` per-proposer gauge.
+pub async fn query_misses_by_proposer(
+    pool: &crate::db::Pool,
+) -> Result<Vec<MissesByProposer>, sqlx::Error> {
+    sqlx::query_file_as!(
+        MissesByProposer,
+        "src/api/metrics_misses_by_proposer_query.sql"
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug)]
+pub struct PropagationDelay {
+    pub proposal_time: NaiveDateTime,
+    pub delay_seconds: i64,
+}
+
+/// Fetch the propagation delay (`proposal_time - tx_quorum_reached`) of
+/// misses recorded since `since`, for the `/metrics` propagation delay
+/// histogram.
+pub async fn query_propagation_delays_since(
+    pool: &crate::db::Pool,
+    since: NaiveDateTime,
+) -> Result<Vec<PropagationDelay>, sqlx::Error> {
+    sqlx::query_file_as!(
+        PropagationDelay,
+        "src/api/metrics_propagation_delays_query.sql",
+        since
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -5,59 +5,97 @@ use chrono::{Duration, NaiveDateTime};
 use serde::Deserialize;
 use sqlx::postgres::types::PgInterval;
 
-use super::{miss_time_tuple::serde_opt_miss_time_tuple, MissTimeTuple, RequestError};
+use crate::analyze::NonInclusionReason;
+
+use super::{miss_time_tuple::serde_opt_time_query, MissTimeTuple, RequestError, TimeQuery};
 
 #[derive(Deserialize, Clone)]
 pub struct MissArgs {
     #[serde(default)]
-    #[serde(with = "serde_opt_miss_time_tuple")]
-    from: Option<MissTimeTuple>,
+    #[serde(with = "serde_opt_time_query")]
+    from: Option<TimeQuery>,
     #[serde(default)]
-    #[serde(with = "serde_opt_miss_time_tuple")]
-    to: Option<MissTimeTuple>,
+    #[serde(with = "serde_opt_time_query")]
+    to: Option<TimeQuery>,
     block_number: Option<i32>,
     proposer_index: Option<i32>,
     sender: Option<String>,
     propagation_time: Option<i64>,
     min_tip: Option<i64>,
+    reason: Option<String>,
+    exclude_reason: Option<String>,
 }
 
 #[derive(Deserialize, Clone)]
 pub struct GroupedMissArgs {
     #[serde(default)]
-    #[serde(with = "serde_opt_miss_time_tuple")]
-    from: Option<MissTimeTuple>,
+    #[serde(with = "serde_opt_time_query")]
+    from: Option<TimeQuery>,
     #[serde(default)]
-    #[serde(with = "serde_opt_miss_time_tuple")]
-    to: Option<MissTimeTuple>,
+    #[serde(with = "serde_opt_time_query")]
+    to: Option<TimeQuery>,
     block_number: Option<i32>,
     proposer_index: Option<i32>,
     sender: Option<String>,
     propagation_time: Option<i64>,
     min_tip: Option<i64>,
     min_num_misses: Option<i64>,
+    reason: Option<String>,
+    exclude_reason: Option<String>,
 }
 
 impl MissArgs {
-    pub fn checked_from(&self) -> Result<MissTimeTuple, RequestError> {
-        Ok(self.from.unwrap_or_else(|| MissTimeTuple {
-            proposal_time: NaiveDateTime::from_timestamp_opt(0, 0).unwrap(),
-            tx_quorum_reached: None,
-        }))
+    /// Build a `MissArgs` from already-parsed arguments. Used by the GraphQL
+    /// resolvers, which accept a `MissFilter` input type rather than
+    /// deserializing query string parameters.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        from: Option<TimeQuery>,
+        to: Option<TimeQuery>,
+        block_number: Option<i32>,
+        proposer_index: Option<i32>,
+        sender: Option<String>,
+        propagation_time: Option<i64>,
+        min_tip: Option<i64>,
+        reason: Option<String>,
+        exclude_reason: Option<String>,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            block_number,
+            proposer_index,
+            sender,
+            propagation_time,
+            min_tip,
+            reason,
+            exclude_reason,
+        }
+    }
+
+    pub fn checked_from(&self, request_time: NaiveDateTime) -> Result<MissTimeTuple, RequestError> {
+        Ok(self.from.map(|t| t.resolve(request_time)).unwrap_or(
+            MissTimeTuple {
+                proposal_time: NaiveDateTime::from_timestamp_opt(0, 0).unwrap(),
+                tx_quorum_reached: None,
+            },
+        ))
     }
 
     pub fn checked_to(&self, request_time: NaiveDateTime) -> Result<MissTimeTuple, RequestError> {
-        Ok(self.to.unwrap_or(MissTimeTuple {
-            proposal_time: request_time,
-            tx_quorum_reached: None,
-        }))
+        Ok(self.to.map(|t| t.resolve(request_time)).unwrap_or(
+            MissTimeTuple {
+                proposal_time: request_time,
+                tx_quorum_reached: None,
+            },
+        ))
     }
 
     pub fn checked_time_range(
         &self,
         request_time: NaiveDateTime,
     ) -> Result<(MissTimeTuple, MissTimeTuple), RequestError> {
-        let from = self.checked_from()?;
+        let from = self.checked_from(request_time)?;
         let to = self.checked_to(request_time)?;
         Ok((min(from, to), max(from, to)))
     }
@@ -66,7 +104,7 @@ impl MissArgs {
         &self,
         request_time: NaiveDateTime,
     ) -> Result<bool, RequestError> {
-        Ok(self.checked_from()? <= self.checked_to(request_time)?)
+        Ok(self.checked_from(request_time)? <= self.checked_to(request_time)?)
     }
 
     pub fn checked_block_number(&self) -> Result<Option<i32>, RequestError> {
@@ -88,9 +126,46 @@ impl MissArgs {
     pub fn checked_min_tip(&self) -> Result<Option<i64>, RequestError> {
         from_opt_nonneg_uint(self.min_tip, String::from("min_tip"))
     }
+
+    pub fn checked_reason(&self) -> Result<Option<NonInclusionReason>, RequestError> {
+        from_opt_reason(&self.reason)
+    }
+
+    pub fn checked_exclude_reason(&self) -> Result<Option<NonInclusionReason>, RequestError> {
+        from_opt_reason(&self.exclude_reason)
+    }
 }
 
 impl GroupedMissArgs {
+    /// Build a `GroupedMissArgs` from already-parsed arguments. See
+    /// `MissArgs::new`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        from: Option<TimeQuery>,
+        to: Option<TimeQuery>,
+        block_number: Option<i32>,
+        proposer_index: Option<i32>,
+        sender: Option<String>,
+        propagation_time: Option<i64>,
+        min_tip: Option<i64>,
+        min_num_misses: Option<i64>,
+        reason: Option<String>,
+        exclude_reason: Option<String>,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            block_number,
+            proposer_index,
+            sender,
+            propagation_time,
+            min_tip,
+            min_num_misses,
+            reason,
+            exclude_reason,
+        }
+    }
+
     pub fn checked_min_num_misses(&self) -> Result<Option<i64>, RequestError> {
         from_opt_nonneg_uint(self.min_num_misses, String::from("min_num_misses"))
     }
@@ -106,10 +181,23 @@ impl From<GroupedMissArgs> for MissArgs {
             sender: m.sender,
             propagation_time: m.propagation_time,
             min_tip: m.min_tip,
+            reason: m.reason,
+            exclude_reason: m.exclude_reason,
         }
     }
 }
 
+fn from_opt_reason(reason: &Option<String>) -> Result<Option<NonInclusionReason>, RequestError> {
+    reason
+        .as_deref()
+        .map(|s| {
+            s.parse().map_err(|_| RequestError::InvalidReason {
+                reason: s.to_string(),
+            })
+        })
+        .transpose()
+}
+
 fn from_opt_interval(
     i: Option<i64>,
     parameter: String,
@@ -1,32 +1,28 @@
-use chrono::NaiveDateTime;
 use serde::Serialize;
 
-use super::{miss_range_bound::serde::miss_range_bound, MissRangeBound};
+use super::miss_time_tuple::{serde_miss_time_tuple, MissTimeTuple};
 
 #[derive(Debug, Serialize)]
 pub struct ItemizedResponse<T> {
     items: Vec<T>,
     complete: bool,
-    #[serde(with = "miss_range_bound")]
-    from: MissRangeBound,
-    #[serde(with = "miss_range_bound")]
-    to: MissRangeBound,
+    #[serde(with = "serde_miss_time_tuple")]
+    from: MissTimeTuple,
+    #[serde(with = "serde_miss_time_tuple")]
+    to: MissTimeTuple,
 }
 
 impl<T> ItemizedResponse<T> {
     pub fn new(
         items: Vec<T>,
         complete: bool,
-        query_from: MissRangeBound,
-        query_to: NaiveDateTime,
-        data_to: Option<MissRangeBound>,
+        query_from: MissTimeTuple,
+        query_to: MissTimeTuple,
+        data_to: Option<MissTimeTuple>,
     ) -> Self {
         #[allow(clippy::unnecessary_unwrap)]
         let to = if complete || data_to.is_none() {
-            MissRangeBound {
-                proposal_time: query_to,
-                offset: None,
-            }
+            query_to
         } else {
             data_to.unwrap()
         };
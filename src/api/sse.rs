@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+use actix_web::{web::Bytes, Error, Result};
+use futures::stream::{self, Stream};
+
+use super::{InternalError, Miss};
+
+/// Serialize `miss` as an SSE `data:` frame.
+pub fn to_sse_event(miss: &Miss) -> Result<Bytes, Error> {
+    let payload = serde_json::to_string(miss).map_err(|_| Error::from(InternalError {}))?;
+    Ok(Bytes::from(format!("data: {}\n\n", payload)))
+}
+
+/// An SSE comment emitted every `interval`, so idle connections aren't closed
+/// by intermediate proxies while waiting for the next real event.
+pub fn keep_alive(interval: Duration) -> impl Stream<Item = Result<Bytes, Error>> {
+    stream::unfold(tokio::time::interval(interval), |mut interval| async move {
+        interval.tick().await;
+        Some((
+            Ok::<Bytes, Error>(Bytes::from_static(b": keep-alive\n\n")),
+            interval,
+        ))
+    })
+}
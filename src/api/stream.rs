@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use actix_web::{
+    get,
+    http::header,
+    web::{self, Query},
+    Error, HttpResponse, Responder, Result,
+};
+use chrono::Utc;
+use futures::stream::{self, StreamExt};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+
+use super::{
+    query_misses, query_misses_since,
+    sse::{keep_alive, to_sse_event},
+    AppState, Miss, MissArgs,
+};
+
+/// How often we poll storage for misses to feed into the live broadcast
+/// channel.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// How often to emit an SSE keep-alive comment so idle connections aren't
+/// closed by intermediate proxies.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+/// Capacity of the broadcast channel. A subscriber that falls behind by more
+/// than this many misses sees a gap rather than blocking publishers.
+const FEED_CAPACITY: usize = 1024;
+
+pub fn new_feed() -> broadcast::Sender<Miss> {
+    let (tx, _) = broadcast::channel(FEED_CAPACITY);
+    tx
+}
+
+/// Poll storage for misses recorded since the last tick and publish them to
+/// `tx`. This is how newly detected misses reach `/v0/misses/stream`
+/// subscribers.
+pub async fn poll_new_misses(pool: crate::db::Pool, tx: broadcast::Sender<Miss>) {
+    let mut since = Utc::now().naive_utc();
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        match query_misses_since(&pool, since).await {
+            Ok(misses) => {
+                if let Some(last) = misses.iter().map(|m| m.proposal_time).max() {
+                    since = last;
+                }
+                for miss in misses {
+                    // Sending fails only when there are no subscribers, which
+                    // is the common case between misses.
+                    let _ = tx.send(miss);
+                }
+            }
+            Err(e) => log::warn!("error polling for new misses: {}", e),
+        }
+    }
+}
+
+/// Stream newly detected misses as Server-Sent Events. An optional `from`
+/// bound (shared with `/v0/misses`) lets a reconnecting client backfill the
+/// gap from storage before the connection switches to the live tail.
+#[get("/v0/misses/stream")]
+pub async fn handle_misses_stream(
+    data: web::Data<AppState>,
+    q: Query<MissArgs>,
+) -> Result<impl Responder, Error> {
+    let backfill = query_misses(
+        &q.0,
+        &data.pool,
+        data.config.api_max_response_rows,
+        data.request_time,
+    )
+    .await?;
+
+    let live = BroadcastStream::new(data.miss_feed.subscribe()).filter_map(|r| async move { r.ok() });
+    let events = stream::iter(backfill).chain(live).map(|miss| to_sse_event(&miss));
+
+    let body = stream::select(events, keep_alive(KEEP_ALIVE_INTERVAL));
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CONTENT_TYPE, "text/event-stream"))
+        .streaming(body))
+}
@@ -5,7 +5,7 @@ use serde::Serialize;
 
 use super::Miss;
 
-#[derive(Serialize, Clone, PartialEq, Eq)]
+#[derive(Serialize, Clone, PartialEq, Eq, async_graphql::SimpleObject)]
 pub struct Tx {
     pub tx_hash: String,
     #[serde(with = "ts_seconds")]
@@ -14,10 +14,11 @@ pub struct Tx {
     pub tx_quorum_reached: NaiveDateTime,
     pub sender: String,
     pub num_misses: usize,
+    #[graphql(name = "blocks")]
     pub misses: Vec<TxMiss>,
 }
 
-#[derive(Serialize, Clone, PartialEq, Eq)]
+#[derive(Serialize, Clone, PartialEq, Eq, async_graphql::SimpleObject)]
 pub struct TxMiss {
     pub block_hash: String,
     pub slot: i32,
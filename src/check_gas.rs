@@ -4,6 +4,7 @@ use ethers::providers::Middleware;
 use crate::{
     analyze::{get_median_tip, get_min_nonzero_tip, get_tip},
     cli::Config,
+    fee_history::check_economic_includability,
     types::{TxHash, U256},
     watch::NodeConfig,
 };
@@ -48,5 +49,11 @@ pub async fn check_gas(config: Config, tx_hash: TxHash, slot: u64) -> Result<()>
     println!("  block gas used: {}", block_gas_used);
     println!("block unused gas: {}", block_gas_limit - block_gas_used);
     println!("    tx gas limit: {}", tx_gas_limit);
+
+    match check_economic_includability(&ep, &tx, exec.block_number.as_u64()).await {
+        Ok(verdict) => println!("  economic verdict: {:?}", verdict),
+        Err(e) => println!("  economic verdict: unavailable ({e})"),
+    }
+
     Ok(())
 }
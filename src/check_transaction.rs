@@ -9,12 +9,15 @@ use ethers::{
 
 use crate::{
     analyze::{
-        check_base_fee_too_low, check_nonce_mismatch, check_not_enough_space, check_tip_too_low,
+        check_base_fee_too_low, check_nonce_mismatch, check_not_enough_space,
+        check_sender_has_code, check_tip_too_low,
     },
     cli::Config,
+    code_cache::CodeCache,
     consensus_api::ConsensusProvider,
+    fee_history::check_economic_includability,
     nonce_cache::NonceCache,
-    types::{BeaconBlock, ExecutionPayload, TxHash, GENESIS_TIME_SECONDS, H256},
+    types::{BeaconBlock, ExecutionPayload, TxHash, H256},
     watch::NodeConfig,
 };
 
@@ -28,8 +31,11 @@ pub async fn check_transaction(
     let consensus_provider = node_config.consensus_provider();
     let mut nonce_cache = NonceCache::new(
         node_config.execution_http_provider(),
+        &config.nonce_quorum_execution_http_urls,
+        config.nonce_quorum_threshold,
         config.nonce_cache_size,
     );
+    let mut code_cache = CodeCache::new(node_config.execution_http_provider(), config.code_cache_size);
 
     let transaction = execution_provider
         .get_transaction(transaction_hash)
@@ -50,6 +56,10 @@ pub async fn check_transaction(
             &execution_provider,
             &consensus_provider,
             &mut nonce_cache,
+            &mut code_cache,
+            config.genesis_time_seconds,
+            config.seconds_per_slot,
+            config.tip_percentile,
         )
         .await?;
         if i < num_blocks {
@@ -65,12 +75,16 @@ pub async fn check_transaction_in_block(
     execution_provider: &Provider<Http>,
     consensus_provider: &ConsensusProvider,
     nonce_cache: &mut NonceCache,
+    code_cache: &mut CodeCache,
+    genesis_time_seconds: i64,
+    seconds_per_slot: u64,
+    tip_percentile: u64,
 ) -> Result<()> {
     let block = execution_provider
         .get_block(block_number)
         .await?
         .ok_or(eyre!("block not found"))?;
-    let slot = (block.timestamp.as_u64() - GENESIS_TIME_SECONDS as u64) / 12;
+    let slot = (block.timestamp.as_u64() - genesis_time_seconds as u64) / seconds_per_slot;
     let beacon_block_without_root = consensus_provider.fetch_beacon_block_by_slot(slot).await?;
     let beacon_block = BeaconBlock::new(beacon_block_without_root, H256::zero());
     let exec = &beacon_block.body.execution_payload;
@@ -78,14 +92,22 @@ pub async fn check_transaction_in_block(
     let replaced = check_replaced(transaction, exec);
     let not_enough_space = check_not_enough_space(transaction, exec);
     let base_fee_too_low = check_base_fee_too_low(transaction, exec)?;
-    let tip_too_low = check_tip_too_low(transaction, exec)?;
+    let tip_too_low = check_tip_too_low(transaction, exec, tip_percentile)?;
     let nonce_mismatch = check_nonce_mismatch(transaction, &beacon_block, nonce_cache).await?;
+    let sender_has_code = check_sender_has_code(transaction, &beacon_block, code_cache).await?;
+    let economic_verdict =
+        check_economic_includability(execution_provider, transaction, block_number).await;
 
     println!("  replaced by others: {replaced}");
     println!("    not enough space: {not_enough_space}");
     println!("    base fee too low: {base_fee_too_low}");
     println!("         tip too low: {tip_too_low}");
     println!("      nonce mismatch: {nonce_mismatch}");
+    println!("     sender has code: {sender_has_code}");
+    match economic_verdict {
+        Ok(verdict) => println!("    economic verdict: {verdict:?}"),
+        Err(e) => println!("    economic verdict: unavailable ({e})"),
+    }
 
     Ok(())
 }
@@ -1,7 +1,10 @@
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
 use clap::{Parser, Subcommand};
-use color_eyre::{eyre::WrapErr, Result};
+use color_eyre::{
+    eyre::{ensure, WrapErr},
+    Result,
+};
 use figment::{
     providers::{Env, Format, Toml},
     Figment,
@@ -66,12 +69,63 @@ pub struct Config {
     #[serde(default = "default_nonce_cache_size")]
     pub nonce_cache_size: usize,
 
+    /// Additional execution node HTTP endpoints to cross-check nonces
+    /// against before trusting one enough to flag a transaction as
+    /// censored. The primary `execution_http_url` is always queried too.
+    #[serde(default)]
+    pub nonce_quorum_execution_http_urls: Vec<url::Url>,
+
+    /// Minimum number of execution providers (including the primary) that
+    /// must agree on a nonce before it's trusted.
+    #[serde(default = "default_nonce_quorum_threshold")]
+    pub nonce_quorum_threshold: usize,
+
+    /// Maximum number of `(block, account)` code-lookup entries to keep
+    /// cached, for the EIP-3607 "sender is a contract" inclusion check.
+    #[serde(default = "default_code_cache_size")]
+    pub code_cache_size: usize,
+
+    /// Percentile (0-100) of a block's included tips a pool transaction must
+    /// meet or exceed to not be considered underpriced, analogous to
+    /// OpenEthereum's `--gas-price-percentile`. Defaults to the median.
+    #[serde(default = "default_tip_percentile")]
+    pub tip_percentile: u64,
+
+    /// Size, in seconds, of the sliding window `compare-providers` uses to
+    /// compute provider agreement ratios.
+    #[serde(default = "default_provider_agreement_window_seconds")]
+    pub provider_agreement_window_seconds: i64,
+
     #[serde(default)]
     pub api_db_connection: String,
     pub api_host: String,
     pub api_port: u16,
     #[serde(default = "default_api_max_response_rows")]
     pub api_max_response_rows: usize,
+
+    /// Maximum number of distinct proposer indices the `/metrics`
+    /// `censorship_misses_by_proposer` gauge will report on, so a validator
+    /// set large enough to be scraped in full can't blow up the series
+    /// count. Proposers are kept in descending order of miss count.
+    #[serde(default = "default_api_metrics_max_distinct_proposers")]
+    pub api_metrics_max_distinct_proposers: usize,
+
+    /// Maximum number of sub-queries a single `/v0/batch` request may bundle,
+    /// so one HTTP call can't fan out into an unbounded number of DB queries.
+    #[serde(default = "default_api_batch_max_queries")]
+    pub api_batch_max_queries: usize,
+
+    /// Unix timestamp of slot 0, used to compute a beacon block's proposal
+    /// time from its slot. Defaults to mainnet genesis; override to run the
+    /// monitor against a testnet or devnet with a different genesis time.
+    #[serde(default = "default_genesis_time_seconds")]
+    pub genesis_time_seconds: i64,
+
+    /// Length of a slot in seconds, used alongside `genesis_time_seconds` to
+    /// compute proposal times. Defaults to mainnet's 12s slots; override for
+    /// chains with a different slot duration.
+    #[serde(default = "default_seconds_per_slot")]
+    pub seconds_per_slot: u64,
 }
 
 impl Config {
@@ -80,10 +134,16 @@ impl Config {
         if let Some(config_path) = config_path {
             config = config.merge(Toml::file(config_path));
         }
-        config
+        let config: Config = config
             .merge(Env::prefixed("MONITOR_"))
             .extract()
-            .wrap_err("error loading config")
+            .wrap_err("error loading config")?;
+        ensure!(
+            config.tip_percentile <= 100,
+            "tip_percentile must be in 0..=100, got {}",
+            config.tip_percentile
+        );
+        Ok(config)
     }
 
     pub fn execution_ws_urls(&self) -> Vec<url::Url> {
@@ -109,6 +169,38 @@ fn default_nonce_cache_size() -> usize {
     1000
 }
 
+fn default_nonce_quorum_threshold() -> usize {
+    1
+}
+
+fn default_code_cache_size() -> usize {
+    1000
+}
+
+fn default_tip_percentile() -> u64 {
+    50
+}
+
+fn default_provider_agreement_window_seconds() -> i64 {
+    60
+}
+
 fn default_api_max_response_rows() -> usize {
     3
 }
+
+fn default_api_metrics_max_distinct_proposers() -> usize {
+    1000
+}
+
+fn default_api_batch_max_queries() -> usize {
+    20
+}
+
+fn default_genesis_time_seconds() -> i64 {
+    crate::types::GENESIS_TIME_SECONDS
+}
+
+fn default_seconds_per_slot() -> u64 {
+    12
+}
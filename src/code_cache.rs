@@ -0,0 +1,104 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Instant,
+};
+
+use ethers::{
+    providers::{Http, Middleware, Provider, ProviderError},
+    types::{BlockId, Transaction},
+};
+use thiserror::Error;
+
+use crate::{
+    metrics,
+    types::{Address, BeaconBlock, H256},
+};
+
+/// A cache from `(beacon block root, account)` to whether that account had
+/// non-empty code as of the block's pre-state, so `check_inclusion` doesn't
+/// have to re-issue an `eth_getCode` call for every pool transaction from a
+/// sender it's already resolved for that block. Unlike `NonceCache`, there's
+/// no per-block state to incrementally derive (an account's code doesn't
+/// change as a side effect of the transactions that precede it in a block),
+/// so entries are looked up and evicted individually rather than per block.
+pub struct CodeCache {
+    entries: HashMap<(H256, Address), bool>,
+    last_access_time: BTreeMap<(H256, Address), Instant>,
+    max_size: usize,
+    provider: Provider<Http>,
+}
+
+#[derive(Debug, Error)]
+pub enum CodeCacheError {
+    #[error("failed to fetch code")]
+    ProviderError(#[from] ProviderError),
+}
+
+impl CodeCache {
+    pub fn new(provider: Provider<Http>, max_size: usize) -> Self {
+        let c = CodeCache {
+            entries: HashMap::new(),
+            last_access_time: BTreeMap::new(),
+            max_size,
+            provider,
+        };
+        c.report();
+        c
+    }
+
+    /// Check whether `account` had non-empty code as of the start of
+    /// `beacon_block`, i.e. whether EIP-3607 would have forbidden it from
+    /// originating a transaction included in that block.
+    pub async fn has_code(
+        &mut self,
+        account: &Address,
+        beacon_block: &BeaconBlock<Transaction>,
+    ) -> Result<bool, CodeCacheError> {
+        let key = (beacon_block.root, *account);
+        if let Some(&has_code) = self.entries.get(&key) {
+            self.touch(key);
+            return Ok(has_code);
+        }
+
+        let has_code = self.fetch(account, beacon_block).await?;
+        self.entries.insert(key, has_code);
+        self.touch(key);
+        self.prune();
+        self.report();
+        Ok(has_code)
+    }
+
+    async fn fetch(
+        &self,
+        account: &Address,
+        beacon_block: &BeaconBlock<Transaction>,
+    ) -> Result<bool, CodeCacheError> {
+        let block_id = Some(BlockId::Hash(
+            beacon_block.body.execution_payload.parent_hash,
+        ));
+        let code = self.provider.get_code(*account, block_id).await?;
+        Ok(!code.0.is_empty())
+    }
+
+    fn touch(&mut self, key: (H256, Address)) {
+        self.last_access_time.insert(key, Instant::now());
+    }
+
+    fn prune(&mut self) {
+        while self.entries.len() > self.max_size {
+            if let Some((oldest_key, _)) = self.last_access_time.pop_first() {
+                self.entries.remove(&oldest_key);
+            } else {
+                log::error!(
+                    "failed to prune code cache: last access time map is empty, but still too \
+                     many resident entries"
+                );
+                break;
+            }
+        }
+    }
+
+    fn report(&self) {
+        metrics::CODE_CACHE_SIZE.set(self.entries.len() as i64);
+    }
+}
@@ -1,8 +1,9 @@
 use std::{
-    collections::HashSet,
+    collections::{HashSet, VecDeque},
     io::{stdout, Write},
 };
 
+use chrono::{DateTime, Duration, Utc};
 use color_eyre::{eyre::eyre, Report, Result};
 use itertools::Itertools;
 use tokio::sync::{
@@ -12,6 +13,7 @@ use tokio::sync::{
 
 use crate::{
     cli,
+    metrics,
     types::TxHash,
     watch::{watch_transactions, Event, NodeConfig},
 };
@@ -41,8 +43,9 @@ pub async fn compare_providers(config: &cli::Config) -> Result<()> {
         Err::<(), Report>(eyre!("watch task ended unexpectedly"))
     });
 
+    let window = Duration::seconds(config.provider_agreement_window_seconds);
     let process_handle = tokio::spawn(async move {
-        process_transactions(&mut rx, n).await?;
+        process_transactions(&mut rx, n, window).await?;
         Err::<(), Report>(eyre!("process task ended unexpectedly"))
     });
 
@@ -54,20 +57,60 @@ pub async fn compare_providers(config: &cli::Config) -> Result<()> {
     Ok(())
 }
 
-async fn process_transactions(rx: &mut Receiver<Event>, n: usize) -> Result<()> {
-    let mut seen_by: Vec<HashSet<TxHash>> = Vec::new();
-    for _ in 0..n {
-        seen_by.push(HashSet::new());
+/// The set of transaction hashes a single provider has seen within the
+/// sliding agreement window, backed by a time-ordered queue so expired
+/// entries are dropped from the front (mirroring the `prune` pattern in
+/// `HeadHistory`). Each dropped entry costs a scan of the remaining queue to
+/// decide whether its hash leaves the membership set.
+struct SeenWindow {
+    entries: VecDeque<(TxHash, DateTime<Utc>)>,
+    members: HashSet<TxHash>,
+}
+
+impl SeenWindow {
+    fn new() -> Self {
+        SeenWindow {
+            entries: VecDeque::new(),
+            members: HashSet::new(),
+        }
+    }
+
+    fn observe(&mut self, hash: TxHash, timestamp: DateTime<Utc>) {
+        self.entries.push_back((hash, timestamp));
+        self.members.insert(hash);
     }
+
+    /// Drop entries older than `cutoff`. A hash is only removed from the
+    /// membership set once its last remaining occurrence in the window has
+    /// expired.
+    fn prune(&mut self, cutoff: DateTime<Utc>) {
+        while let Some(&(hash, timestamp)) = self.entries.front() {
+            if timestamp > cutoff {
+                break;
+            }
+            self.entries.pop_front();
+            if !self.entries.iter().any(|&(h, _)| h == hash) {
+                self.members.remove(&hash);
+            }
+        }
+    }
+}
+
+async fn process_transactions(rx: &mut Receiver<Event>, n: usize, window: Duration) -> Result<()> {
+    let mut seen_by: Vec<SeenWindow> = (0..n).map(|_| SeenWindow::new()).collect();
     let mut i = 0;
     while let Some(event) = rx.recv().await {
         match event {
             Event::NewTransaction {
                 node,
                 hash,
-                timestamp: _,
+                timestamp,
             } => {
-                seen_by[node].insert(hash);
+                seen_by[node].observe(hash, timestamp);
+                let cutoff = timestamp - window;
+                for w in &mut seen_by {
+                    w.prune(cutoff);
+                }
             }
             _ => {
                 return Err(eyre!("received non-transaction event"));
@@ -79,17 +122,32 @@ async fn process_transactions(rx: &mut Receiver<Event>, n: usize) -> Result<()>
             print!("\r");
             for k in 1..(n + 1) {
                 for combination in (0..n).combinations(k) {
-                    let mut union = seen_by[combination[0]].clone();
-                    let mut intersection = seen_by[combination[0]].clone();
+                    let mut union = seen_by[combination[0]].members.clone();
+                    let mut intersection = seen_by[combination[0]].members.clone();
                     for j in &combination[1..] {
-                        union.extend(&seen_by[*j]);
-                        intersection.retain(|e| seen_by[*j].contains(e));
+                        union.extend(&seen_by[*j].members);
+                        intersection.retain(|e| seen_by[*j].members.contains(e));
                     }
-                    print!(
-                        "{:>8} {:.2}",
-                        intersection.len(),
-                        (intersection.len() as f64) / (union.len() as f64),
-                    );
+                    let jaccard = if union.is_empty() {
+                        0.
+                    } else {
+                        (intersection.len() as f64) / (union.len() as f64)
+                    };
+
+                    let label: String =
+                        itertools::Itertools::intersperse(
+                            combination.iter().map(|i| i.to_string()),
+                            String::from("|"),
+                        )
+                        .collect();
+                    metrics::PROVIDER_AGREEMENT_INTERSECTION
+                        .with_label_values(&[label.as_str()])
+                        .set(intersection.len() as i64);
+                    metrics::PROVIDER_AGREEMENT_JACCARD
+                        .with_label_values(&[label.as_str()])
+                        .set(jaccard);
+
+                    print!("{:>8} {:.2}", intersection.len(), jaccard);
                 }
             }
             stdout().flush().unwrap();
@@ -20,7 +20,7 @@ pub async fn truncate(pool: &Pool) -> Result<(), sqlx::Error> {
     log::debug!("truncating db");
     sqlx::query!(
         r#"
-        TRUNCATE miss, transaction, beacon_block RESTART IDENTITY;
+        TRUNCATE miss, transaction, non_inclusion_reason_count, beacon_block RESTART IDENTITY;
         "#
     )
     .execute(pool)
@@ -28,7 +28,12 @@ pub async fn truncate(pool: &Pool) -> Result<(), sqlx::Error> {
     Ok(())
 }
 
-pub async fn insert_analysis_into_db(analysis: &Analysis, pool: &Pool) -> Result<(), sqlx::Error> {
+pub async fn insert_analysis_into_db(
+    analysis: &Analysis,
+    pool: &Pool,
+    genesis_time_seconds: i64,
+    seconds_per_slot: u64,
+) -> Result<(), sqlx::Error> {
     log::debug!("persisting analysis for block {}", analysis.beacon_block);
 
     let mut tx = pool.begin().await?;
@@ -44,14 +49,20 @@ pub async fn insert_analysis_into_db(analysis: &Analysis, pool: &Pool) -> Result
             proposer_index,
             execution_block_hash,
             execution_block_number,
-            proposal_time
+            proposal_time,
+            pool_size,
+            gas_used,
+            gas_limit
         ) VALUES (
             $1,
             $2,
             $3,
             $4,
             $5,
-            $6
+            $6,
+            $7,
+            $8,
+            $9
         ) ON CONFLICT DO NOTHING;
         "#,
         beacon_root_str,
@@ -59,11 +70,35 @@ pub async fn insert_analysis_into_db(analysis: &Analysis, pool: &Pool) -> Result
         block.proposer_index.as_u64() as i64,
         encode_hex_prefixed(exec.block_hash),
         exec.block_number.as_u64() as i64,
-        block.proposal_time().naive_utc(),
+        block.proposal_time(genesis_time_seconds, seconds_per_slot).naive_utc(),
+        analysis.num_txs_in_pool as i32,
+        exec.gas_used.as_u64() as i64,
+        exec.gas_limit.as_u64() as i64,
     )
     .execute(&mut tx)
     .await?;
 
+    for (reason, count) in &analysis.non_inclusion_reasons {
+        sqlx::query!(
+            r#"
+            INSERT INTO data.non_inclusion_reason_count (
+                beacon_block_root,
+                reason,
+                count
+            ) VALUES (
+                $1,
+                $2,
+                $3
+            ) ON CONFLICT DO NOTHING;
+            "#,
+            beacon_root_str,
+            reason.as_str(),
+            *count as i32,
+        )
+        .execute(&mut tx)
+        .await?;
+    }
+
     for missing_transaction in analysis.missing_transactions.values() {
         let transaction_hash_str = encode_hex_prefixed(missing_transaction.transaction.hash);
         let queries = [
@@ -102,7 +137,10 @@ pub async fn insert_analysis_into_db(analysis: &Analysis, pool: &Pool) -> Result
             "#,
                 transaction_hash_str,
                 beacon_root_str,
-                analysis.beacon_block.proposal_time().naive_utc(),
+                analysis
+                    .beacon_block
+                    .proposal_time(genesis_time_seconds, seconds_per_slot)
+                    .naive_utc(),
                 missing_transaction.tip,
             ),
         ];
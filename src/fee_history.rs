@@ -0,0 +1,86 @@
+use ethers::{
+    providers::{Http, Middleware, Provider, ProviderError},
+    types::BlockNumber,
+};
+use thiserror::Error;
+
+use crate::{
+    analyze::{get_tip, TransactionError},
+    types::{Transaction, U256},
+};
+
+/// Priority-fee percentiles requested from `eth_feeHistory` to characterize
+/// what tip was actually competitive in a block. The lowest percentile is
+/// treated as the tip a transaction needed to offer to be includable.
+const REWARD_PERCENTILES: [f64; 3] = [10.0, 50.0, 90.0];
+
+#[derive(Debug, Error)]
+pub enum FeeHistoryError {
+    #[error("failed to fetch fee history")]
+    ProviderError(#[from] ProviderError),
+    #[error("transaction fee fields couldn't be read")]
+    TransactionError(#[from] TransactionError),
+    #[error("node returned fee history with no base fee entry for block {0}")]
+    MissingBaseFee(u64),
+}
+
+/// Whether a transaction's offered tip was economically competitive for a
+/// given block, distinguishing underpricing from true censorship.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EconomicVerdict {
+    /// The transaction's max fee didn't even cover the block's base fee; no
+    /// tip could have made it includable.
+    BelowBaseFee,
+    /// The max fee covered the base fee, but the effective tip offered was
+    /// below the lowest tip `eth_feeHistory` reports as having been paid.
+    BelowObservedMinimumTip,
+    /// The offered tip was competitive; non-inclusion isn't explained by
+    /// underpricing.
+    Competitive,
+}
+
+/// Determine whether `transaction` would have been economically competitive
+/// for inclusion in `block_number`, using `eth_feeHistory` to learn the
+/// block's base fee and the priority fees that were actually paid, rather
+/// than re-deriving them from the full block body. Handles legacy and
+/// EIP-1559 transactions uniformly via `get_tip`, and falls back to treating
+/// the observed minimum tip as zero if the node returns an empty `reward`
+/// array.
+pub async fn check_economic_includability(
+    provider: &Provider<Http>,
+    transaction: &Transaction,
+    block_number: u64,
+) -> Result<EconomicVerdict, FeeHistoryError> {
+    let history = provider
+        .fee_history(
+            1u64,
+            BlockNumber::Number(block_number.into()),
+            &REWARD_PERCENTILES,
+        )
+        .await?;
+
+    let base_fee = *history
+        .base_fee_per_gas
+        .first()
+        .ok_or(FeeHistoryError::MissingBaseFee(block_number))?;
+
+    let tip = match get_tip(transaction, base_fee) {
+        Ok(tip) => tip,
+        Err(TransactionError::FeeTooLow { .. }) => return Ok(EconomicVerdict::BelowBaseFee),
+        Err(e) => return Err(FeeHistoryError::TransactionError(e)),
+    };
+
+    let min_observed_tip = history
+        .reward
+        .as_ref()
+        .and_then(|block_rewards| block_rewards.first())
+        .and_then(|percentiles| percentiles.first())
+        .copied()
+        .unwrap_or(U256::zero());
+
+    if tip < min_observed_tip {
+        Ok(EconomicVerdict::BelowObservedMinimumTip)
+    } else {
+        Ok(EconomicVerdict::Competitive)
+    }
+}
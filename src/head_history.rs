@@ -21,9 +21,15 @@ impl HeadHistory {
     }
 
     /// Insert a new block into the history observed at the given timestamp.
-    pub fn observe(&mut self, timestamp: DateTime<Utc>, head: BeaconBlock<Transaction>) {
+    pub fn observe(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        head: BeaconBlock<Transaction>,
+        genesis_time_seconds: i64,
+        seconds_per_slot: u64,
+    ) {
         let i = self.0.partition_point(|oh| oh.timestamp <= timestamp);
-        let dt = timestamp - head.proposal_time();
+        let dt = timestamp - head.proposal_time(genesis_time_seconds, seconds_per_slot);
         if dt < Duration::zero() {
             log::warn!(
                 "received block {} {:2}s before proposal time",
@@ -124,7 +130,7 @@ mod test {
         };
 
         for o in vec![&o0, &o1, &o2] {
-            h.observe(o.timestamp, o.head.clone());
+            h.observe(o.timestamp, o.head.clone(), 0, 12);
         }
 
         assert!(h.at(t0).is_none());
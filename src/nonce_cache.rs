@@ -8,116 +8,220 @@ use ethers::{
     types::{BlockId, Transaction},
 };
 use thiserror::Error;
+use url::Url;
 
 use crate::{
     metrics,
     types::{Address, BeaconBlock, H256},
 };
 
+/// An LRU cache from beacon block root to that block's per-account nonce
+/// map, so queries about recent-but-not-head blocks (e.g. from the `check`
+/// CLI command, which walks back over the last few blocks) can be served
+/// without erroring, falling back to the provider only for blocks that
+/// aren't (or are no longer) resident.
+///
+/// Nonces are fetched from every configured execution endpoint concurrently
+/// and only trusted once `quorum_threshold` of them agree, so a single
+/// lagging or faulty node can't make the monitor flag a transaction as
+/// censored based on a stale nonce.
 pub struct NonceCache {
-    beacon_block: BeaconBlock<Transaction>,
-    nonces: HashMap<Address, u64>,
-    last_access_time: BTreeMap<Address, Instant>,
+    blocks: HashMap<H256, HashMap<Address, u64>>,
+    last_access_time: BTreeMap<H256, Instant>,
+    head: H256,
     max_size: usize,
-    provider: Provider<Http>,
+    providers: Vec<Provider<Http>>,
+    quorum_threshold: usize,
 }
 
 #[derive(Debug, Error)]
 pub enum NonceCacheError {
     #[error("failed to fetch nonce")]
     ProviderError(#[from] ProviderError),
-    #[error("nonce cache is at block hash {internal}, but was queried at {queried}")]
-    WrongBlockError { internal: H256, queried: H256 },
+    #[error(
+        "only {responses} of {participants} execution providers answered, and none of them \
+         agreed {required} times, so the nonce is inconclusive"
+    )]
+    NoQuorumError {
+        responses: usize,
+        participants: usize,
+        required: usize,
+    },
 }
 
 impl NonceCache {
-    pub fn new(provider: Provider<Http>, max_size: usize) -> Self {
+    /// `primary` is always queried; `extra_endpoints` are additional
+    /// execution nodes to cross-check its answer against.
+    pub fn new(
+        primary: Provider<Http>,
+        extra_endpoints: &[Url],
+        quorum_threshold: usize,
+        max_size: usize,
+    ) -> Self {
+        let mut providers = vec![primary];
+        for url in extra_endpoints {
+            providers.push(
+                Provider::try_from(url.as_str()).expect("invalid nonce quorum execution url"),
+            );
+        }
+
         let c = NonceCache {
-            beacon_block: BeaconBlock::default(),
-            nonces: HashMap::new(),
+            blocks: HashMap::new(),
             last_access_time: BTreeMap::new(),
+            head: H256::zero(),
             max_size,
-            provider,
+            providers,
+            quorum_threshold,
         };
         c.report();
         c
     }
 
+    /// Get `account`'s nonce as of the end of `beacon_block`, i.e. with the
+    /// block's own transactions applied. If
+    /// `beacon_block` is still resident in the cache, look it up there,
+    /// lazily fetching and caching it if this is the first query for that
+    /// account at that block. If `beacon_block` was evicted (or was never
+    /// applied, e.g. because it's being queried directly rather than via
+    /// `apply_block`), fall back to a plain provider fetch without growing
+    /// the cache.
     pub async fn get(
         &mut self,
         account: &Address,
         beacon_block: &BeaconBlock<Transaction>,
     ) -> Result<u64, NonceCacheError> {
-        if beacon_block.root != self.beacon_block.root {
-            return Err(NonceCacheError::WrongBlockError {
-                internal: self.beacon_block.root,
-                queried: beacon_block.root,
-            });
+        if self.blocks.contains_key(&beacon_block.root) {
+            self.touch(beacon_block.root);
+            if let Some(&n) = self.blocks[&beacon_block.root].get(account) {
+                return Ok(n);
+            }
+
+            let nonce = self.fetch(account, beacon_block).await?;
+            self.blocks
+                .get_mut(&beacon_block.root)
+                .expect("just checked it's present")
+                .insert(*account, nonce);
+            self.report();
+            return Ok(nonce);
         }
 
-        self.last_access_time.insert(*account, Instant::now());
+        self.fetch(account, beacon_block).await
+    }
 
+    /// Query every configured execution provider for `account`'s nonce at
+    /// `beacon_block` and only return a value once `quorum_threshold` of
+    /// them agree on it. Disagreements are recorded via
+    /// `metrics::NONCE_QUORUM_DISAGREEMENTS` so a node drifting out of sync
+    /// shows up on dashboards even while quorum is still being reached.
+    async fn fetch(
+        &self,
+        account: &Address,
+        beacon_block: &BeaconBlock<Transaction>,
+    ) -> Result<u64, NonceCacheError> {
         let block_id = Some(BlockId::Hash(
             beacon_block.body.execution_payload.block_hash,
         ));
-        match self.nonces.get(account) {
-            Some(&n) => Ok(n),
-            None => {
-                let nonce_u256 = self
-                    .provider
-                    .get_transaction_count(*account, block_id)
-                    .await
-                    .map_err(NonceCacheError::ProviderError)?;
-                let nonce = nonce_u256.as_u64();
-                self.nonces.insert(*account, nonce);
-                self.prune();
-                self.report();
-                Ok(nonce)
+
+        let responses = futures::future::join_all(
+            self.providers
+                .iter()
+                .map(|p| p.get_transaction_count(*account, block_id)),
+        )
+        .await;
+
+        let mut tally: HashMap<u64, usize> = HashMap::new();
+        for r in &responses {
+            match r {
+                Ok(n) => *tally.entry(n.as_u64()).or_insert(0) += 1,
+                Err(e) => log::warn!("execution provider error while fetching nonce: {}", e),
             }
         }
+        let num_responses: usize = tally.values().sum();
+
+        if tally.len() > 1 {
+            metrics::NONCE_QUORUM_DISAGREEMENTS.inc();
+            log::warn!(
+                "execution providers disagree on nonce for {:?} at block {}: {:?}",
+                account,
+                beacon_block,
+                tally,
+            );
+        }
+
+        match tally.into_iter().max_by_key(|&(_, count)| count) {
+            Some((nonce, count)) if count >= self.quorum_threshold => Ok(nonce),
+            _ => Err(NonceCacheError::NoQuorumError {
+                responses: num_responses,
+                participants: self.providers.len(),
+                required: self.quorum_threshold,
+            }),
+        }
     }
 
+    /// Derive and cache the nonce map for the new head block from its
+    /// parent's, applying the nonces of the transactions it includes. If the
+    /// parent isn't resident (it was evicted, or this is a reorg away from
+    /// the previous head), start from an empty map rather than clearing the
+    /// rest of the cache: other branches' entries are left untouched and
+    /// simply age out of the LRU on their own.
     pub fn apply_block(&mut self, beacon_block: BeaconBlock<Transaction>) {
-        if beacon_block.parent_root != self.beacon_block.root {
+        if beacon_block.parent_root != self.head {
             log::info!(
-                "clearing nonce cache due to reorg from {} to {}",
-                self.beacon_block,
+                "nonce cache head moved from {} to {}, which isn't its child (reorg); deriving \
+                 from parent {} if still resident",
+                self.head,
                 beacon_block,
+                beacon_block.parent_root,
             );
-            self.nonces.clear();
         }
-        self.beacon_block = beacon_block;
+
+        let mut nonces = self
+            .blocks
+            .get(&beacon_block.parent_root)
+            .cloned()
+            .unwrap_or_default();
 
         let mut num_modified = 0;
-        for tx in &self.beacon_block.body.execution_payload.transactions {
-            self.nonces.entry(tx.from).and_modify(|n| {
+        for tx in &beacon_block.body.execution_payload.transactions {
+            nonces.entry(tx.from).and_modify(|n| {
                 *n = tx.nonce.as_u64() + 1;
                 num_modified += 1;
             });
         }
-        self.report();
+
         log::debug!(
             "applied block {} to nonce cache, updating {} of {} entries",
-            self.beacon_block,
+            beacon_block,
             num_modified,
-            self.nonces.len(),
+            nonces.len(),
         );
+
+        self.head = beacon_block.root;
+        self.blocks.insert(beacon_block.root, nonces);
+        self.touch(beacon_block.root);
+        self.prune();
+        self.report();
+    }
+
+    fn touch(&mut self, root: H256) {
+        self.last_access_time.insert(root, Instant::now());
     }
 
     fn prune(&mut self) {
-        while self.nonces.len() > self.max_size {
-            if let Some(oldest_account) = self.last_access_time.pop_first() {
-                self.nonces.remove(&oldest_account.0);
+        while self.blocks.len() > self.max_size {
+            if let Some((oldest_root, _)) = self.last_access_time.pop_first() {
+                self.blocks.remove(&oldest_root);
             } else {
                 log::error!(
                     "failed to prune nonce cache: last access time map is empty, but still too \
-                     many nonces"
+                     many resident blocks"
                 );
+                break;
             }
         }
     }
 
     fn report(&self) {
-        metrics::NONCE_CACHE_SIZE.set(self.nonces.len() as i64);
+        metrics::NONCE_CACHE_SIZE.set(self.blocks.len() as i64);
     }
 }
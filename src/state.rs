@@ -4,6 +4,7 @@ use ethers::types::Transaction;
 use crate::{
     analyze::{analyze, Analysis},
     cli::Config,
+    code_cache::CodeCache,
     head_history::HeadHistory,
     nonce_cache::NonceCache,
     pool::Pool,
@@ -17,11 +18,15 @@ pub struct State {
     pool: Pool,
     head_history: HeadHistory,
     nonce_cache: NonceCache,
+    code_cache: CodeCache,
 
     analysis_queue: Vec<BeaconBlock<Transaction>>,
 
     quorum: usize,
     propagation_time: chrono::Duration,
+    genesis_time_seconds: i64,
+    seconds_per_slot: u64,
+    tip_percentile: u64,
 }
 
 impl State {
@@ -31,17 +36,27 @@ impl State {
 
         let node_config = NodeConfig::from(config);
         let nonce_cache_provider = node_config.execution_http_provider();
-        let nonce_cache = NonceCache::new(nonce_cache_provider, config.nonce_cache_size);
+        let nonce_cache = NonceCache::new(
+            nonce_cache_provider,
+            &config.nonce_quorum_execution_http_urls,
+            config.nonce_quorum_threshold,
+            config.nonce_cache_size,
+        );
+        let code_cache = CodeCache::new(node_config.execution_http_provider(), config.code_cache_size);
 
         State {
             pool,
             head_history,
             nonce_cache,
+            code_cache,
 
             analysis_queue: Vec::new(),
 
             quorum: node_config.execution_ws_urls.len(),
             propagation_time: chrono::Duration::seconds(config.propagation_time),
+            genesis_time_seconds: config.genesis_time_seconds,
+            seconds_per_slot: config.seconds_per_slot,
+            tip_percentile: config.tip_percentile,
         }
     }
 
@@ -107,7 +122,12 @@ impl State {
         beacon_block: BeaconBlock<Transaction>,
         t: DateTime<Utc>,
     ) -> Vec<Analysis> {
-        self.head_history.observe(t, beacon_block.clone());
+        self.head_history.observe(
+            t,
+            beacon_block.clone(),
+            self.genesis_time_seconds,
+            self.seconds_per_slot,
+        );
         self.head_history
             .prune(t - Duration::seconds(PRUNE_DELAY_SECONDS));
         self.analysis_queue.push(beacon_block);
@@ -120,7 +140,8 @@ impl State {
     ) -> Option<Analysis> {
         self.nonce_cache.apply_block(beacon_block.clone());
 
-        let proposal_time = beacon_block.proposal_time();
+        let proposal_time =
+            beacon_block.proposal_time(self.genesis_time_seconds, self.seconds_per_slot);
         let head_obs = self.head_history.at(proposal_time);
         match head_obs {
             None => {
@@ -149,8 +170,12 @@ impl State {
             beacon_block,
             &self.pool,
             &mut self.nonce_cache,
+            &mut self.code_cache,
             self.quorum,
             self.propagation_time,
+            self.genesis_time_seconds,
+            self.seconds_per_slot,
+            self.tip_percentile,
         )
         .await;
         match analysis {
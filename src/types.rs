@@ -37,6 +37,19 @@ where
     T::from_dec_str(&s).map_err(serde::de::Error::custom)
 }
 
+/// Like `from_dec_str`, but for fields that are only present from a given
+/// fork onwards (e.g. the Deneb blob gas fields), so older blocks simply omit
+/// them.
+fn option_from_dec_str<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: FromDecStr,
+    D: serde::Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    s.map(|s| T::from_dec_str(&s).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
 #[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct NewBeaconHeadEvent {
     #[serde(deserialize_with = "from_dec_str")]
@@ -108,9 +121,12 @@ impl<T> BeaconBlock<T> {
         }
     }
 
-    pub fn proposal_time(&self) -> DateTime<Utc> {
-        Utc.timestamp_opt(GENESIS_TIME_SECONDS, 0).unwrap()
-            + Duration::seconds((self.slot.as_u64() * 12) as i64)
+    /// The wall-clock time this block's slot was proposed at, computed from
+    /// `genesis_time_seconds` and `seconds_per_slot` so the monitor can run
+    /// against chains other than mainnet (see `Config`).
+    pub fn proposal_time(&self, genesis_time_seconds: i64, seconds_per_slot: u64) -> DateTime<Utc> {
+        Utc.timestamp_opt(genesis_time_seconds, 0).unwrap()
+            + Duration::seconds((self.slot.as_u64() * seconds_per_slot) as i64)
     }
 }
 
@@ -179,6 +195,30 @@ pub struct ExecutionPayload<T> {
     pub base_fee_per_gas: U256,
     pub block_hash: H256,
     pub transactions: Vec<T>,
+    /// Total blob gas consumed by this block's transactions. `None` for
+    /// blocks before the Deneb fork.
+    #[serde(default, deserialize_with = "option_from_dec_str")]
+    pub blob_gas_used: Option<U64>,
+    /// Running total of blob gas consumed in excess of the target, used to
+    /// derive the blob base fee. `None` for blocks before the Deneb fork.
+    #[serde(default, deserialize_with = "option_from_dec_str")]
+    pub excess_blob_gas: Option<U64>,
+    /// Withdrawals processed by this block. `None` for blocks before the
+    /// Capella fork.
+    #[serde(default)]
+    pub withdrawals: Option<Vec<Withdrawal>>,
+}
+
+/// A validator withdrawal included in a post-Capella `ExecutionPayload`.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Withdrawal {
+    #[serde(deserialize_with = "from_dec_str")]
+    pub index: U64,
+    #[serde(deserialize_with = "from_dec_str")]
+    pub validator_index: U64,
+    pub address: Address,
+    #[serde(deserialize_with = "from_dec_str")]
+    pub amount: U64,
 }
 
 impl<T> ExecutionPayload<T> {
@@ -198,6 +238,9 @@ impl<T> ExecutionPayload<T> {
             base_fee_per_gas: e.base_fee_per_gas,
             block_hash: e.block_hash,
             transactions: txs,
+            blob_gas_used: e.blob_gas_used,
+            excess_blob_gas: e.excess_blob_gas,
+            withdrawals: e.withdrawals,
         }
     }
 }